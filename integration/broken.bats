@@ -0,0 +1,80 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+@test "broken: lists binaries and scripts with invalid docs" {
+  fixture "broken"
+
+  run main --help
+
+  assert_success
+  assert_line "    binary    "
+  assert_line "    hello     Say hello"
+  assert_line "    latin1    "
+}
+
+@test "broken: shows help for binaries" {
+  fixture "broken"
+
+  run main --help binary
+
+  assert_success
+  assert_line "Usage: main binary [args]..."
+}
+
+@test "broken: reports docs that aren't UTF-8" {
+  fixture "broken"
+
+  run main --help latin1
+
+  assert_failure 65
+  assert_output "main: error: invalid UTF-8"
+}
+
+@test "broken: skips file names that aren't UTF-8" {
+  fixture "broken"
+
+  touch "$SUB_TEST_DIR/broken/libexec/$(printf 'bad\xff')"
+  chmod +x "$SUB_TEST_DIR/broken/libexec/$(printf 'bad\xff')"
+
+  run main --commands
+
+  assert_success
+  assert_output "binary
+hello
+latin1"
+}
+
+@test "broken: validate reports invalid files" {
+  fixture "broken"
+
+  touch "$SUB_TEST_DIR/broken/libexec/$(printf 'bad\xff')"
+  chmod +x "$SUB_TEST_DIR/broken/libexec/$(printf 'bad\xff')"
+
+  run main --validate
+
+  assert_failure 1
+  assert_line --partial "/libexec/bad"
+  assert_line --partial ": warning: invalid UTF-8"
+  assert_line "$SUB_TEST_DIR/broken/libexec/latin1: error: invalid UTF-8"
+}
+
+@test "broken: reports scripts that can't be read" {
+  if [ "$(id -u)" -eq 0 ]; then
+    skip "root can read any file"
+  fi
+
+  fixture "broken"
+
+  chmod 111 "$SUB_TEST_DIR/broken/libexec/hello"
+
+  run main --commands
+
+  assert_success
+  assert_line "hello"
+
+  run main hello
+
+  assert_failure 126
+  assert_output --partial "main: error: Permission denied"
+}
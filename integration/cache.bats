@@ -0,0 +1,115 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+ECHO="$SUB_TEST_DIR/project/libexec/echo"
+
+# changes the summary of echo without changing its size or modification time, which only shows
+# up once echo is parsed again
+change_summary_in_place() {
+  touch -r "$ECHO" "$SUB_TEST_DIR/reference"
+  sed -i.bak 's/# Summary: Echo arguments/# Summary: Echo argumentz/' "$ECHO"
+  rm "$ECHO.bak"
+  touch -r "$SUB_TEST_DIR/reference" "$ECHO"
+}
+
+@test "cache: keeps the index out of the CLI's cache directory" {
+  fixture "project"
+
+  run main --help
+
+  assert_success
+  [ -f "$XDG_CACHE_HOME/sub/main/index.json" ]
+  [ -z "$(ls -A "$XDG_CACHE_HOME/main/cache")" ]
+}
+
+@test "cache: uses the index for unchanged files" {
+  fixture "project"
+
+  main --help
+  change_summary_in_place
+
+  run main --help
+
+  assert_success
+  assert_line "    echo                   Echo arguments"
+}
+
+@test "cache: parses changed files again" {
+  fixture "project"
+
+  main --help
+  sed -i.bak 's/# Summary: Echo arguments/# Summary: Echo all arguments/' "$ECHO"
+  rm "$ECHO.bak"
+
+  run main --help
+
+  assert_success
+  assert_line "    echo                   Echo all arguments"
+}
+
+@test "cache: ignores an unreadable index" {
+  fixture "project"
+
+  mkdir -p "$XDG_CACHE_HOME/sub/main"
+  echo "not json" > "$XDG_CACHE_HOME/sub/main/index.json"
+
+  run main --help
+
+  assert_success
+  assert_line "    echo                   Echo arguments"
+}
+
+@test "cache: --no-cache neither reads nor writes the index" {
+  fixture "project"
+
+  main --help
+  change_summary_in_place
+
+  run main --no-cache --help
+
+  assert_success
+  assert_line "    echo                   Echo argumentz"
+
+  run main --help
+
+  assert_success
+  assert_line "    echo                   Echo arguments"
+}
+
+@test "cache: --rebuild-cache parses every command again" {
+  fixture "project"
+
+  main --help
+  change_summary_in_place
+
+  run main --rebuild-cache
+
+  assert_success
+  assert_output ""
+
+  run main --help
+
+  assert_success
+  assert_line "    echo                   Echo argumentz"
+}
+
+@test "cache: --no-cache conflicts with --rebuild-cache" {
+  fixture "project"
+
+  run main --no-cache --rebuild-cache
+
+  assert_failure 64
+}
+
+@test "cache: listing commands doesn't parse their usage" {
+  fixture "project"
+
+  main --commands --format json
+  main --tree
+  main --tree --flat
+
+  run grep -q '"spec":{' "$XDG_CACHE_HOME/sub/main/index.json"
+
+  assert_failure
+}
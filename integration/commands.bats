@@ -8,9 +8,9 @@ load test_helper
   run main --commands
 
   assert_success
-  assert_output "a.sh
+  assert_output "a
 b
-c.other
+c
 invalid-usage
 nested"
 }
@@ -21,7 +21,7 @@ nested"
   run main --commands --extension=sh
 
   assert_success
-  assert_output "a.sh"
+  assert_output "a"
 }
 
 @test "commands: lists nested commands" {
@@ -42,3 +42,75 @@ double"
   assert_success
   assert_output "e"
 }
+
+@test "commands: lists subcommands recursively" {
+  fixture "commands"
+
+  run main --commands --recursive nested
+
+  assert_success
+  assert_output "d
+double
+double e"
+}
+
+@test "commands: filter commands by glob" {
+  fixture "commands"
+
+  run main --commands --recursive --glob "nested d*"
+
+  assert_success
+  assert_output "nested d
+nested double
+nested double e"
+}
+
+@test "commands: prints commands as json" {
+  fixture "commands"
+
+  run main --commands --format json nested
+
+  assert_success
+  assert_line '    "name": "double",'
+  assert_line '    "command": "main nested double",'
+  assert_line '    "type": "directory",'
+  assert_line '    "type": "file",'
+  assert_line '    "alias_of": null,'
+}
+
+@test "commands: marks aliases in json" {
+  fixture "lint"
+
+  run main --commands --format json --glob alias
+
+  assert_success
+  assert_line '    "name": "alias",'
+  assert_line '    "type": "alias",'
+  assert_line '    "alias_of": "valid",'
+}
+
+@test "commands: rejects an invalid glob" {
+  fixture "commands"
+
+  run main --commands --glob "["
+
+  assert_failure 64
+  assert_output --partial "invalid value '[' for '--glob <pattern>'"
+}
+
+@test "commands: prints an empty json array without matches" {
+  fixture "commands"
+
+  run main --commands --format json --glob "missing"
+
+  assert_success
+  assert_output "[]"
+}
+
+@test "commands: format requires commands" {
+  fixture "commands"
+
+  run main --format json
+
+  assert_failure 64
+}
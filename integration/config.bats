@@ -0,0 +1,116 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+PROJECT_DIR="$SUB_TEST_DIR/config"
+
+@test "config: reads the CLI name from sub.toml" {
+  fixture "config"
+
+  run $SUB_BIN --absolute "$PROJECT_DIR" -- env _MAIN_ROOT
+
+  assert_success
+  assert_output "$PROJECT_DIR"
+}
+
+@test "config: --name overrides the name in sub.toml" {
+  fixture "config"
+
+  run $SUB_BIN --name other --absolute "$PROJECT_DIR" -- env _OTHER_ROOT
+
+  assert_success
+  assert_output "$PROJECT_DIR"
+}
+
+@test "config: requires a name when sub.toml doesn't set one" {
+  fixture "project"
+
+  run $SUB_BIN --absolute "$SUB_TEST_DIR/project" -- echo
+
+  assert_failure 2
+  assert_output --partial "--name must be given unless it is set in sub.toml"
+}
+
+@test "config: reads the color from sub.toml" {
+  fixture "config"
+
+  run $SUB_BIN --absolute "$PROJECT_DIR" -- not-found
+
+  assert_failure
+  assert_output --partial $'\e[31merror:'
+}
+
+@test "config: --color overrides the color in sub.toml" {
+  fixture "config"
+
+  run $SUB_BIN --color never --absolute "$PROJECT_DIR" -- not-found
+
+  assert_failure
+  assert_output "main: error: no such sub command 'not-found'"
+}
+
+@test "config: reads infer-subcommands from sub.toml" {
+  fixture "config"
+
+  run $SUB_BIN --absolute "$PROJECT_DIR" -- en GREETING
+
+  assert_success
+  assert_output "hello from sub.toml"
+}
+
+@test "config: --no-infer-subcommands overrides sub.toml" {
+  fixture "config"
+
+  run $SUB_BIN --color never --no-infer-subcommands --absolute "$PROJECT_DIR" -- en GREETING
+
+  assert_failure 127
+  assert_output --partial "main: error: no such sub command 'en'"
+}
+
+@test "config: sets extra environment variables from sub.toml" {
+  fixture "config"
+
+  run $SUB_BIN --absolute "$PROJECT_DIR" -- env GREETING
+
+  assert_success
+  assert_output "hello from sub.toml"
+}
+
+@test "config: reads interpreters from sub.toml" {
+  fixture "config"
+
+  run $SUB_BIN --absolute "$PROJECT_DIR" -- notes
+
+  assert_success
+  assert_output "# Summary: Plain text notes"
+}
+
+@test "config: --interpreter overrides interpreters from sub.toml" {
+  fixture "config"
+
+  run $SUB_BIN --absolute "$PROJECT_DIR" --interpreter "txt=echo notes" -- notes
+
+  assert_success
+  assert_output "notes $PROJECT_DIR/libexec/notes.txt"
+}
+
+@test "config: reads the default help behavior from sub.toml" {
+  fixture "config"
+
+  run $SUB_BIN --absolute "$PROJECT_DIR"
+
+  assert_success
+  assert_output "env
+notes"
+}
+
+@test "config: rejects an invalid sub.toml" {
+  fixture "config"
+
+  echo 'unknown-setting = true' > "$PROJECT_DIR/sub.toml"
+
+  run $SUB_BIN --name main --color never --absolute "$PROJECT_DIR" -- env
+
+  assert_failure 78
+  assert_output --partial "main: error: invalid $PROJECT_DIR/sub.toml: unknown field \`unknown-setting\`"
+}
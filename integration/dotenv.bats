@@ -0,0 +1,95 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+@test "dotenv: loads .env from the CLI root" {
+  fixture "dotenv"
+
+  run main env FROM_ROOT
+
+  assert_success
+  assert_output "root"
+
+  run main env QUOTED
+
+  assert_success
+  assert_output "two words"
+}
+
+@test "dotenv: .env files override sub.toml" {
+  fixture "dotenv"
+
+  run main env FROM_TOML
+
+  assert_success
+  assert_output "toml"
+
+  run main env OVERRIDDEN
+
+  assert_success
+  assert_output "root"
+}
+
+@test "dotenv: the caller's environment overrides .env files and sub.toml" {
+  fixture "dotenv"
+
+  FROM_ROOT=caller run main env FROM_ROOT
+
+  assert_success
+  assert_output "caller"
+
+  FROM_TOML=caller run main env FROM_TOML
+
+  assert_success
+  assert_output "caller"
+
+  # set in the caller, in sub.toml and in .env
+  OVERRIDDEN=caller run main env OVERRIDDEN
+
+  assert_success
+  assert_output "caller"
+}
+
+@test "dotenv: the user's .env overrides the root's" {
+  fixture "dotenv"
+  mkdir -p "$XDG_CONFIG_HOME/main"
+  echo "OVERRIDDEN=user" > "$XDG_CONFIG_HOME/main/.env"
+
+  run main env OVERRIDDEN
+
+  assert_success
+  assert_output "user"
+}
+
+@test "dotenv: the current directory's .env overrides the others when enabled" {
+  fixture "dotenv"
+  mkdir -p "$XDG_CONFIG_HOME/main"
+  echo "OVERRIDDEN=user" > "$XDG_CONFIG_HOME/main/.env"
+  cd "$SUB_TEST_DIR/dotenv/work"
+
+  run main env OVERRIDDEN
+
+  assert_success
+  assert_output "cwd"
+}
+
+@test "dotenv: ignores the current directory's .env by default" {
+  fixture "dotenv"
+  rm "$SUB_TEST_DIR/dotenv/sub.toml"
+  cd "$SUB_TEST_DIR/dotenv/work"
+
+  run main env OVERRIDDEN
+
+  assert_success
+  assert_output "root"
+}
+
+@test "dotenv: fails on an invalid .env file" {
+  fixture "dotenv"
+  echo "not a variable" >> "$SUB_TEST_DIR/dotenv/.env"
+
+  run main env FROM_ROOT
+
+  assert_failure 78
+  assert_output "main: error: invalid $SUB_TEST_DIR/dotenv/.env: line 5: expected KEY=VALUE"
+}
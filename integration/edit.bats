@@ -68,7 +68,7 @@ load test_helper
   run main --edit not-found
 
   assert_failure
-  assert_output "main: no such sub command 'not-found'"
+  assert_output "main: error: no such sub command 'not-found'"
 }
 
 @test "edit: fails gracefully when no editor is configured" {
@@ -21,5 +21,87 @@ load test_helper
   run main env _MAIN_CACHE
 
   assert_success
-  assert_output "$HOME/.cache/main/cache"
+  assert_output "$XDG_CACHE_HOME/main/cache"
+}
+
+@test "env: sets an env variable with the CLI name" {
+  fixture "project"
+
+  run main env _MAIN_NAME
+
+  assert_success
+  assert_output "main"
+}
+
+@test "env: sets an env variable with the CLI executable" {
+  fixture "project"
+
+  run main env _MAIN_EXECUTABLE
+
+  if [ $(uname) = "Darwin" ]; then
+    prefix="/private"
+  fi
+
+  assert_success
+  assert_output "$prefix${SUB_TEST_DIR}/project/bin/main"
+}
+
+@test "env: leaves the CLI executable unset without --executable" {
+  fixture "project"
+
+  run $SUB_BIN --name main --absolute "$SUB_TEST_DIR/project" -- env _MAIN_EXECUTABLE
+
+  assert_success
+  assert_output ""
+}
+
+@test "env: sets env variables with the command path and name" {
+  fixture "project"
+
+  run main env _MAIN_COMMAND
+
+  assert_success
+  assert_output "main env"
+
+  run main env _MAIN_COMMAND_NAME
+
+  assert_success
+  assert_output "env"
+}
+
+@test "env: sets env variables for XDG config, data and state directories" {
+  fixture "project"
+
+  run main env _MAIN_CONFIG
+
+  assert_success
+  assert_output "$SUB_TEST_DIR/xdg/config/main"
+
+  run main env _MAIN_DATA
+
+  assert_success
+  assert_output "$SUB_TEST_DIR/xdg/data/main"
+
+  run main env _MAIN_STATE
+
+  assert_success
+  assert_output "$SUB_TEST_DIR/xdg/state/main"
+}
+
+@test "env: creates the XDG directories only when running a command" {
+  fixture "project"
+
+  run main --help
+
+  assert_success
+  [ ! -e "$SUB_TEST_DIR/xdg/config" ]
+  [ ! -e "$SUB_TEST_DIR/xdg/data" ]
+  [ ! -e "$SUB_TEST_DIR/xdg/state" ]
+
+  run main env _MAIN_CONFIG
+
+  assert_success
+  [ -d "$SUB_TEST_DIR/xdg/config/main" ]
+  [ -d "$SUB_TEST_DIR/xdg/data/main" ]
+  [ -d "$SUB_TEST_DIR/xdg/state/main" ]
 }
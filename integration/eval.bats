@@ -0,0 +1,78 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+@test "eval: prints shell code when not run through the shell function" {
+  fixture "eval"
+
+  run main goto /tmp
+
+  assert_success
+  assert_output "cd '/tmp'
+export GOTO_SHELL=''"
+}
+
+@test "eval: evaluates the output of eval commands in the calling shell" {
+  fixture "eval"
+
+  run bash -c 'eval "$(main --init bash)"; main goto /tmp; echo "$PWD $GOTO_SHELL"'
+
+  assert_success
+  assert_output "/tmp bash"
+}
+
+@test "eval: keeps the output of other commands" {
+  fixture "eval"
+
+  run bash -c 'eval "$(main --init bash)"; main hello; echo "$PWD"'
+
+  assert_success
+  assert_output "hello
+$PWD"
+}
+
+@test "eval: passes the exit code through the shell function" {
+  fixture "eval"
+
+  run bash -c 'eval "$(main --init bash)"; main not-found'
+
+  assert_failure 127
+}
+
+@test "eval: registers bash completions" {
+  fixture "eval"
+
+  run bash -c 'eval "$(main --init bash)"; COMP_WORDS=(main g); COMP_CWORD=1; _main_complete; echo "${COMPREPLY[@]}"'
+
+  assert_success
+  assert_output "goto"
+}
+
+@test "eval: defines a zsh function" {
+  fixture "eval"
+
+  run main --init zsh
+
+  assert_success
+  assert_line "main() {"
+  assert_line "  compdef _main_complete main"
+}
+
+@test "eval: defines a fish function" {
+  fixture "eval"
+
+  run main --init fish
+
+  assert_success
+  assert_line "function main"
+  assert_line "complete -c main -f -a '(_main_complete)'"
+}
+
+@test "eval: rejects unknown shells" {
+  fixture "eval"
+
+  run main --init tcsh
+
+  assert_failure 64
+  assert_output --partial "invalid value 'tcsh' for '--init <shell>'"
+}
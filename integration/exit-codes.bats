@@ -0,0 +1,103 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+PROJECT_DIR="$SUB_TEST_DIR/project"
+
+@test "exit-codes: invalid arguments to a command exit with 64" {
+  fixture "project"
+
+  run main valid-usage
+
+  assert_failure 64
+}
+
+@test "exit-codes: help for a command exits with 0" {
+  fixture "project"
+
+  run main valid-usage --help
+
+  assert_success
+  assert_line --partial "Usage: main valid-usage"
+}
+
+@test "exit-codes: missing libexec directory exits with 66" {
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR"
+
+  assert_failure 66
+}
+
+@test "exit-codes: missing libexec directory exits with 66 even when plugins exist" {
+  mkdir -p "$XDG_DATA_HOME/main/plugins" "$PROJECT_DIR"
+  printf '#!/usr/bin/env bash\n# Summary: A plugin\n' > "$XDG_DATA_HOME/main/plugins/plugin"
+  chmod +x "$XDG_DATA_HOME/main/plugins/plugin"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR"
+
+  assert_failure 66
+}
+
+@test "exit-codes: invalid usage comment exits with 78" {
+  fixture "project"
+
+  run main invalid-usage
+
+  assert_failure 78
+}
+
+@test "exit-codes: non-executable command exits with 126" {
+  fixture "project"
+
+  run main non-exec
+
+  assert_failure 126
+  assert_output "main: error: 'non-exec' is not executable; add +x or map its extension with --interpreter"
+}
+
+@test "exit-codes: command that cannot be executed exits with 126" {
+  fixture "project"
+
+  run main invalid-exec
+
+  assert_failure 126
+}
+
+@test "exit-codes: unknown command exits with 127" {
+  fixture "project"
+
+  run main not-found
+
+  assert_failure 127
+}
+
+@test "exit-codes: ambiguous command exits with 127" {
+  fixture "project"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR" --infer-subcommands -- e
+
+  assert_failure 127
+}
+
+@test "exit-codes: command terminated by a signal exits with 128 + signal" {
+  fixture "project"
+
+  run main signal
+
+  assert_failure 143
+}
+
+@test "exit-codes: completions terminated by a signal terminate sub with the same signal" {
+  fixture "project"
+  cat > "$PROJECT_DIR/libexec/signal-completions" <<'SCRIPT'
+#!/usr/bin/env bash
+# Provide completions
+kill -TERM $$
+SCRIPT
+  chmod +x "$PROJECT_DIR/libexec/signal-completions"
+
+  # bash only reports children that were killed by a signal
+  run bash -c 'main --completions signal-completions; true'
+
+  assert_success
+  assert_output --partial "Terminated"
+}
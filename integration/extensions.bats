@@ -0,0 +1,76 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+@test "extensions: invokes a command without its extension" {
+  fixture "extensions"
+
+  run main build arg
+
+  assert_success
+  assert_output "build.sh arg"
+}
+
+@test "extensions: invokes a command with its extension" {
+  fixture "extensions"
+
+  run main build.sh arg
+
+  assert_success
+  assert_output "build.sh arg"
+}
+
+@test "extensions: prefers an exact name over an extension-less match" {
+  fixture "extensions"
+
+  run main status arg
+
+  assert_success
+  assert_output "status arg"
+}
+
+@test "extensions: lists commands without extensions unless names collide" {
+  fixture "extensions"
+
+  run main --commands
+
+  assert_success
+  assert_output "build
+deploy.py
+deploy.sh
+report
+status
+status.sh"
+}
+
+@test "extensions: completes commands without extensions" {
+  fixture "extensions"
+
+  run main --completions
+
+  assert_success
+  assert_output "$(main --commands)"
+}
+
+@test "extensions: shows commands without extensions in help" {
+  fixture "extensions"
+
+  run main --help
+
+  assert_success
+  assert_line "    build        Run build.sh"
+  assert_line "    report       Run report.py"
+}
+
+@test "extensions: lists the candidates when names without extension collide" {
+  fixture "extensions"
+
+  run main deploy
+
+  assert_failure 127
+  assert_output "main: error: ambiguous sub command 'deploy'
+
+It could be one of these:
+    deploy.py
+    deploy.sh"
+}
@@ -0,0 +1,5 @@
+#!/usr/bin/env bash
+#
+# Summary: Run build.sh
+
+echo "build.sh $*"
@@ -0,0 +1,5 @@
+#!/usr/bin/env bash
+#
+# Summary: Run deploy.py
+
+echo "deploy.py $*"
@@ -0,0 +1,5 @@
+#!/usr/bin/env bash
+#
+# Summary: Run deploy.sh
+
+echo "deploy.sh $*"
@@ -0,0 +1,5 @@
+#!/usr/bin/env bash
+#
+# Summary: Run report.py
+
+echo "report.py $*"
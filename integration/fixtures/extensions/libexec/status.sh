@@ -0,0 +1,5 @@
+#!/usr/bin/env bash
+#
+# Summary: Run status.sh
+
+echo "status.sh $*"
@@ -0,0 +1,3 @@
+# Summary: Say hello without an execute bit
+
+echo "hello $*"
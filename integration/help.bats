@@ -30,17 +30,30 @@ Options:
       --completions            Print completions
       --validate               Validate subcommand
   -e, --edit                   Edit command in $VISUAL or $EDITOR
+      --which                  Print where the command comes from
+      --man <directory>        Write man pages for the command and its subcommands
+      --markdown               Print a Markdown reference for the command and its subcommands
+      --dump-spec              Print the command and its subcommands as JSON
+      --tree                   Print the command and its subcommands as a tree
+      --flat                   Print the full names of all commands instead of a tree
+      --search <terms>         Search commands by keywords in their names and documentation
+      --init <shell>           Print shell code that sets up the CLI [possible values: bash, zsh, fish]
+      --rebuild-cache          Parse the command and its subcommands again and update the cache
+      --no-cache               Parse commands without using the cache
       --commands               Print subcommands
       --extension <extension>  Filter subcommands by extension
+      --recursive              List subcommands of subcommands too
+      --glob <pattern>         Filter subcommands by a glob on their name
+      --format <format>        Output format of subcommands [default: text] [possible values: text, json]
 
 Description of the top level command.
 
 Extended documentation.
 
 Available subcommands:
-    a.sh             A sh script
+    a                A sh script
     b                
-    c.other          
+    c                
     invalid-usage    
     nested           '
 }
@@ -88,7 +101,7 @@ The help section can span multiple lines."
   run main --help not-found
 
   assert_failure
-  assert_output "main: no such sub command 'not-found'"
+  assert_output "main: error: no such sub command 'not-found'"
 }
 
 @test "help: displays help for a directory command" {
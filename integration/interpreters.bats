@@ -0,0 +1,85 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+PROJECT_DIR="$SUB_TEST_DIR/interpreters"
+
+@test "interpreters: runs non-executable scripts through the interpreter for their extension" {
+  fixture "interpreters"
+
+  run main hello.sh world
+
+  assert_success
+  assert_output "hello world"
+}
+
+@test "interpreters: runs scripts without typing their extension" {
+  fixture "interpreters"
+
+  run main hello world
+
+  assert_success
+  assert_output "hello world"
+}
+
+@test "interpreters: lists non-executable scripts with an interpreter" {
+  fixture "interpreters"
+
+  run main --commands
+
+  assert_success
+  assert_output "hello"
+}
+
+@test "interpreters: rejects non-executable scripts without an interpreter" {
+  fixture "interpreters"
+
+  run main unmapped.xyz
+
+  assert_failure 126
+}
+
+@test "interpreters: --interpreter maps extensions to commands" {
+  fixture "interpreters"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR" --interpreter txt=cat -- notes
+
+  assert_success
+  assert_output "# Summary: Plain text notes"
+}
+
+@test "interpreters: --interpreter overrides the default interpreters" {
+  fixture "interpreters"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR" --interpreter "sh=echo running" -- hello world
+
+  assert_success
+  assert_output "running $PROJECT_DIR/libexec/hello.sh world"
+}
+
+@test "interpreters: --interpreter requires EXTENSION=COMMAND" {
+  fixture "interpreters"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR" --interpreter txt -- notes
+
+  assert_failure 2
+}
+
+@test "interpreters: validate accepts scripts run through an interpreter" {
+  fixture "interpreters"
+
+  run main --validate
+
+  assert_success
+  assert_output "$PROJECT_DIR/libexec/notes.txt: warning: non-executable
+$PROJECT_DIR/libexec/unmapped.xyz: warning: non-executable"
+}
+
+@test "interpreters: validate reports missing interpreters" {
+  fixture "interpreters"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR" --interpreter sh=missing-interpreter -- --validate hello
+
+  assert_failure
+  assert_output "$PROJECT_DIR/libexec/hello.sh: error: interpreter 'missing-interpreter' not found"
+}
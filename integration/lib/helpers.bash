@@ -3,3 +3,17 @@ fixture() {
   cp -r "${SUB_ROOT}/integration/fixtures/$name" "$SUB_TEST_DIR"
   export PATH="${SUB_TEST_DIR}/$name/bin:$PATH"
 }
+
+wait_for_output() {
+  file="$1"
+  text="$2"
+
+  for _ in $(seq 1 50); do
+    if grep -q "$text" "$file" 2>/dev/null; then
+      return 0
+    fi
+    sleep 0.1
+  done
+
+  return 1
+}
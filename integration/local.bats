@@ -0,0 +1,94 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+@test "local: runs a command from the project around the current directory" {
+  fixture "local"
+  cd "$SUB_TEST_DIR/local/repo"
+
+  run main test arg
+
+  assert_success
+  assert_output "project test arg"
+}
+
+@test "local: finds the project from a subdirectory" {
+  fixture "local"
+  mkdir -p "$SUB_TEST_DIR/local/repo/src/nested"
+  cd "$SUB_TEST_DIR/local/repo/src/nested"
+
+  run main tasks lint
+
+  assert_success
+  assert_output "project lint "
+}
+
+@test "local: the CLI's own commands take precedence over the project's" {
+  fixture "local"
+  cd "$SUB_TEST_DIR/local/repo"
+
+  run main build
+
+  assert_success
+  assert_output "global build "
+}
+
+@test "local: ignores project commands outside of the project" {
+  fixture "local"
+  cd "$SUB_TEST_DIR/local"
+
+  run main test
+
+  assert_failure 127
+  assert_output "main: error: no such sub command 'test'"
+}
+
+@test "local: marks project commands in --commands" {
+  fixture "local"
+  cd "$SUB_TEST_DIR/local/repo"
+
+  run main --commands
+
+  assert_success
+  assert_output "build
+status
+tasks (project)
+test (project)"
+}
+
+@test "local: marks project commands in help" {
+  fixture "local"
+  cd "$SUB_TEST_DIR/local/repo"
+
+  run main --help
+
+  assert_success
+  assert_output --partial "Available subcommands:
+    build              Build anything
+    status             Show the status
+    tasks (project)    Repository tasks
+    test (project)     Test this repository"
+}
+
+@test "local: completes project commands without the marker" {
+  fixture "local"
+  cd "$SUB_TEST_DIR/local/repo"
+
+  run main --completions
+
+  assert_success
+  assert_output "build
+status
+tasks
+test"
+}
+
+@test "local: prints where a project command comes from" {
+  fixture "local"
+  cd "$SUB_TEST_DIR/local/repo"
+
+  run main --which test
+
+  assert_success
+  assert_output "$SUB_TEST_DIR/local/repo/.main/libexec/test"
+}
@@ -0,0 +1,101 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+@test "man: writes a page for every command" {
+  fixture "project"
+
+  run main --man "$SUB_TEST_DIR/man"
+
+  assert_success
+  assert_line "$SUB_TEST_DIR/man/main.1"
+  assert_line "$SUB_TEST_DIR/man/main-directory.1"
+  assert_line "$SUB_TEST_DIR/man/main-directory-double-with-help.1"
+  assert_line "$SUB_TEST_DIR/man/main-with-help.1"
+  [ -f "$SUB_TEST_DIR/man/main-directory-double-with-help.1" ]
+}
+
+@test "man: writes pages for a subtree" {
+  fixture "project"
+
+  run main --man "$SUB_TEST_DIR/man" directory double
+
+  assert_success
+  assert_output "$SUB_TEST_DIR/man/main-directory-double.1
+$SUB_TEST_DIR/man/main-directory-double-with-help.1"
+}
+
+@test "man: documents a script" {
+  fixture "project"
+
+  main --man "$SUB_TEST_DIR/man" with-help
+
+  run cat "$SUB_TEST_DIR/man/main-with-help.1"
+
+  assert_success
+  assert_output '.TH "MAIN\-WITH\-HELP" "1"
+.SH NAME
+main\-with\-help \- Command with complete help
+.SH SYNOPSIS
+main with\-help <positional> [args]...
+.SH DESCRIPTION
+This is a complete test script with documentation.
+.PP
+The help section can span multiple lines.
+.SH ARGUMENTS
+.TP
+\fI<positional>\fR
+A positional argument
+.TP
+\fI[args]...\fR'
+}
+
+@test "man: documents options" {
+  fixture "project"
+
+  main --man "$SUB_TEST_DIR/man" valid-usage
+
+  run cat "$SUB_TEST_DIR/man/main-valid-usage.1"
+
+  assert_success
+  assert_line '\fB\-u\fR'
+  assert_line '\fB\-\-value\fR \fIVALUE\fR'
+}
+
+@test "man: documents a directory with its README and subcommands" {
+  fixture "project"
+
+  main --man "$SUB_TEST_DIR/man" directory
+
+  run cat "$SUB_TEST_DIR/man/main-directory.1"
+
+  assert_success
+  assert_output '.TH "MAIN\-DIRECTORY" "1"
+.SH NAME
+main\-directory \- A directory subcommand
+.SH SYNOPSIS
+main directory [commands_with_args]...
+.SH DESCRIPTION
+Documentation for this group.
+.PP
+Extended documentation.
+.SH COMMANDS
+.TP
+\fBmain\-directory\-double\fR(1)
+Run a double nested command
+.TP
+\fBmain\-directory\-with\-help\fR(1)
+Help 2'
+}
+
+@test "man: writes pages under a symlinked libexec" {
+  fixture "project"
+  mv "$SUB_TEST_DIR/project/libexec" "$SUB_TEST_DIR/project/real"
+  ln -s real "$SUB_TEST_DIR/project/libexec"
+
+  run main --man "$SUB_TEST_DIR/man"
+
+  assert_success
+  assert_line "$SUB_TEST_DIR/man/main.1"
+  assert_line "$SUB_TEST_DIR/man/main-echo.1"
+}
@@ -0,0 +1,94 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+@test "markdown: renders a reference for a directory and everything under it" {
+  fixture "project"
+
+  run main --markdown directory
+
+  assert_success
+  assert_output '# main directory
+
+A directory subcommand
+
+```
+Usage: main directory [commands_with_args]...
+```
+
+Documentation for this group.
+
+Extended documentation.
+
+## Contents
+
+- [main directory double](#main-directory-double): Run a double nested command
+  - [main directory double with-help](#main-directory-double-with-help): Help 3
+- [main directory with-help](#main-directory-with-help): Help 2
+
+## main directory double
+
+Run a double nested command
+
+```
+Usage: main directory double [commands_with_args]...
+```
+
+Documentation for this double nested group.
+
+Extended documentation.
+
+## main directory double with-help
+
+Help 3
+
+```
+Usage: main directory double with-help [args]...
+```
+
+This is a complete test script with documentation.
+
+The help section can span multiple lines.
+
+| Argument | Description |
+| -------- | ----------- |
+| `[args]...` |  |
+
+## main directory with-help
+
+Help 2
+
+```
+Usage: main directory with-help [args]...
+```
+
+This is a complete test script with documentation.
+
+The help section can span multiple lines.
+
+| Argument | Description |
+| -------- | ----------- |
+| `[args]...` |  |'
+}
+
+@test "markdown: documents arguments and options" {
+  fixture "project"
+
+  run main --markdown valid-usage
+
+  assert_success
+  assert_line '| `<positional>` |  |'
+  assert_line '| `-u` |  |'
+  assert_line '| `--value <VALUE>` |  |'
+}
+
+@test "markdown: links every command from the contents" {
+  fixture "project"
+
+  run main --markdown
+
+  assert_success
+  assert_line "- [main directory](#main-directory): A directory subcommand"
+  assert_line "    - [main directory double with-help](#main-directory-double-with-help): Help 3"
+  assert_line "## main nested echo"
+}
@@ -33,7 +33,7 @@ Available subcommands:
   run main nested not-found
 
   assert_failure
-  assert_output "main: no such sub command 'not-found'"
+  assert_output "main: error: no such sub command 'not-found'"
 }
 
 @test "nested: with a subcommand, runs it" {
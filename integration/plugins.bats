@@ -0,0 +1,109 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+setup() {
+  export XDG_DATA_HOME="$SUB_TEST_DIR/plugins/data"
+}
+
+@test "plugins: lists commands from every libexec path" {
+  fixture "plugins"
+
+  run main --commands
+
+  assert_success
+  assert_output "deploy
+hello
+share
+tools"
+}
+
+@test "plugins: runs a command from a shared libexec path" {
+  fixture "plugins"
+
+  run main share arg
+
+  assert_success
+  assert_output "team share arg"
+}
+
+@test "plugins: runs a command from the plugins directory" {
+  fixture "plugins"
+
+  run main hello arg
+
+  assert_success
+  assert_output "plugin hello arg"
+}
+
+@test "plugins: the project's libexec shadows shared commands" {
+  fixture "plugins"
+
+  run main deploy
+
+  assert_success
+  assert_output "project deploy "
+}
+
+@test "plugins: merges directories with the same name" {
+  fixture "plugins"
+
+  run main --commands tools
+
+  assert_success
+  assert_output "format
+lint"
+
+  run main tools format arg
+
+  assert_success
+  assert_output "team format arg"
+}
+
+@test "plugins: documents a merged directory with the first README" {
+  fixture "plugins"
+
+  run main --help tools
+
+  assert_success
+  assert_line "Project tools"
+  refute_line "Team tools"
+}
+
+@test "plugins: prints where a command comes from" {
+  fixture "plugins"
+
+  run main --which share
+
+  assert_success
+  assert_output "$SUB_TEST_DIR/plugins/team/share"
+}
+
+@test "plugins: prints every directory merged into a group" {
+  fixture "plugins"
+
+  run main --which tools
+
+  assert_success
+  assert_output "$SUB_TEST_DIR/plugins/libexec/tools
+$SUB_TEST_DIR/plugins/team/tools"
+}
+
+@test "plugins: libexec paths on the command line come before sub.toml" {
+  fixture "plugins"
+
+  run $SUB_BIN --name main --absolute "$SUB_TEST_DIR/plugins" --libexec-path "$SUB_TEST_DIR/plugins/extra" -- share
+
+  assert_success
+  assert_output "extra share "
+}
+
+@test "plugins: validate warns about shadowed commands" {
+  fixture "plugins"
+
+  run main --validate
+
+  assert_success
+  assert_output "$SUB_TEST_DIR/plugins/data/main/plugins/share: warning: shadowed by $SUB_TEST_DIR/plugins/team/share
+$SUB_TEST_DIR/plugins/team/deploy: warning: shadowed by $SUB_TEST_DIR/plugins/libexec/deploy"
+}
@@ -0,0 +1,53 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+@test "search: ranks matches in names above matches in documentation" {
+  fixture "project"
+
+  run main --search help
+
+  assert_success
+  assert_output "main directory double with-help    Help 3
+main directory with-help           Help 2
+main with-help                     Command with complete help
+main echo                          Echo arguments
+main nested double echo            Echo arguments 3
+main nested echo                   Echo arguments 2"
+}
+
+@test "search: requires every term to match" {
+  fixture "project"
+
+  run main --search "environment variable"
+
+  assert_success
+  assert_output "main env    Print the value of an environment variable"
+}
+
+@test "search: ignores case" {
+  fixture "project"
+
+  run main --search "ENVIRONMENT"
+
+  assert_success
+  assert_output "main env    Print the value of an environment variable"
+}
+
+@test "search: searches under a subcommand" {
+  fixture "project"
+
+  run main --search help directory double
+
+  assert_success
+  assert_output "main directory double with-help    Help 3"
+}
+
+@test "search: fails when nothing matches" {
+  fixture "project"
+
+  run main --search nothing-like-this
+
+  assert_failure 1
+  assert_output "main: error: no commands match 'nothing-like-this'"
+}
@@ -0,0 +1,45 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+PROJECT_DIR="$SUB_TEST_DIR/signals"
+
+@test "signals: forwards SIGTERM to a running completion script" {
+  fixture "signals"
+
+  $SUB_BIN --name main --absolute "$PROJECT_DIR" -- --completions trap-signals > "$SUB_TEST_DIR/output" &
+  pid=$!
+
+  wait_for_output "$SUB_TEST_DIR/output" "ready"
+  kill -TERM "$pid"
+
+  wait "$pid" && status=0 || status=$?
+
+  assert_equal "$status" 3
+  assert_equal "$(cat "$SUB_TEST_DIR/output")" "ready
+received TERM"
+}
+
+@test "signals: forwards SIGHUP to a running completion script" {
+  fixture "signals"
+
+  $SUB_BIN --name main --absolute "$PROJECT_DIR" -- --completions trap-signals > "$SUB_TEST_DIR/output" &
+  pid=$!
+
+  wait_for_output "$SUB_TEST_DIR/output" "ready"
+  kill -HUP "$pid"
+
+  wait "$pid" && status=0 || status=$?
+
+  assert_equal "$status" 4
+  assert_equal "$(cat "$SUB_TEST_DIR/output")" "ready
+received HUP"
+}
+
+@test "signals: reports the signal that terminated a completion script" {
+  fixture "signals"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR" -- --completions terminate
+
+  assert_failure 143
+}
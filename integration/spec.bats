@@ -0,0 +1,93 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+@test "spec: dumps a command as JSON" {
+  fixture "completions"
+
+  run main --dump-spec literal
+
+  assert_success
+  assert_output "$(cat <<JSON
+{
+  "version": 1,
+  "name": "main",
+  "command": {
+    "name": "literal",
+    "command": "main literal",
+    "type": "file",
+    "paths": [
+      "$SUB_TEST_DIR/completions/libexec/literal"
+    ],
+    "alias_of": null,
+    "summary": null,
+    "description": null,
+    "usage": "main literal <option>",
+    "eval": false,
+    "arguments": [
+      {
+        "name": "option",
+        "kind": "positional",
+        "short": null,
+        "long": null,
+        "required": true,
+        "exclusive": false,
+        "multiple": false,
+        "value_name": "option",
+        "description": "Description of option",
+        "completion": {
+          "type": "command",
+          "command": "echo itworks"
+        }
+      }
+    ],
+    "subcommands": []
+  }
+}
+JSON
+)"
+}
+
+@test "spec: describes script completions" {
+  fixture "completions"
+
+  run main --dump-spec with-completions
+
+  assert_success
+  assert_output --partial '"completion": {
+          "type": "script"
+        }'
+}
+
+@test "spec: includes the whole tree" {
+  fixture "project"
+
+  run main --dump-spec
+
+  assert_success
+  assert_line '  "version": 1,'
+  assert_line '    "type": "directory",'
+  assert_line '                "command": "main directory double with-help",'
+}
+
+@test "spec: marks aliases with their target" {
+  fixture "lint"
+
+  run main --dump-spec alias
+
+  assert_success
+  assert_line '    "type": "alias",'
+  assert_line '    "alias_of": "valid",'
+}
+
+@test "spec: a symlinked libexec is not an alias" {
+  fixture "project"
+  mv "$SUB_TEST_DIR/project/libexec" "$SUB_TEST_DIR/project/real"
+  ln -s real "$SUB_TEST_DIR/project/libexec"
+
+  run main --dump-spec
+
+  assert_success
+  assert_line '    "alias_of": null,'
+  assert_line '        "command": "main echo",'
+}
@@ -7,7 +7,7 @@ PROJECT_DIR="$SUB_TEST_DIR/project"
   run $SUB_BIN --name main --absolute "$PROJECT_DIR"
 
   assert_failure
-  assert_output "main: libexec directory not found in root"
+  assert_output "main: error: libexec directory not found in root"
 }
 
 @test "sub: reject --executable and --absolute given together" {
@@ -57,3 +57,64 @@ PROJECT_DIR="$SUB_TEST_DIR/project"
   assert_success
   assert_output 'name "pos" u "false" long "true" value "thing" args "ex1 ex2 --more"'
 }
+
+@test "sub: --autocorrect runs the only command starting with an unknown name" {
+  fixture "project"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR" --autocorrect -- ech arg
+
+  assert_success
+  assert_output "main: warning: no such sub command 'ech', running 'echo'
+arg"
+}
+
+@test "sub: --autocorrect fails when the unknown name is ambiguous" {
+  fixture "project"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR" --autocorrect -- en
+
+  assert_failure
+  assert_output --partial "main: error: no such sub command 'en'"
+}
+
+@test "sub: --infer-subcommands resolves unambiguous prefixes at each level" {
+  fixture "project"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR" --infer-subcommands -- dir dou with-h arg
+
+  assert_success
+  assert_output "arg"
+}
+
+@test "sub: --infer-subcommands lists the candidates of an ambiguous prefix" {
+  fixture "project"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR" --infer-subcommands -- e
+
+  assert_failure
+  assert_output "main: error: ambiguous sub command 'e'
+
+It could be one of these:
+    echo
+    env
+    env-args
+    error"
+}
+
+@test "sub: --infer-subcommands prefers exact matches" {
+  fixture "project"
+
+  run $SUB_BIN --name main --absolute "$PROJECT_DIR" --infer-subcommands -- env _MAIN_ROOT
+
+  assert_success
+  assert_output "$PROJECT_DIR"
+}
+
+@test "sub: colors error prefixes with --color always" {
+  fixture "project"
+
+  run $SUB_BIN --color always --name main --absolute "$PROJECT_DIR" -- not-found
+
+  assert_failure
+  assert_output $'main: \e[1m\e[31merror:\e[0m no such sub command \'not-found\''
+}
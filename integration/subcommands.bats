@@ -70,7 +70,7 @@ load test_helper
   run main not-found
 
   assert_failure
-  assert_output "main: no such sub command 'not-found'"
+  assert_output "main: error: no such sub command 'not-found'"
 }
 
 @test "subcommands: handles invalid shebang lines gracefully" {
@@ -79,7 +79,7 @@ load test_helper
   run main invalid-exec
 
   assert_failure
-  assert_output "main: Exec format error (os error 8)"
+  assert_output "main: error: missing shebang"
 }
 
 @test "subcommands: handles missing interpreters gracefully" {
@@ -88,5 +88,58 @@ load test_helper
   run main missing-interpreter
 
   assert_failure
-  assert_output "main: No such file or directory (os error 2)"
+  assert_output "main: error: No such file or directory (os error 2)"
+}
+
+@test "subcommands: suggests close matches for unknown commands" {
+  fixture "project"
+
+  run main ecoh
+
+  assert_failure
+  assert_output "main: error: no such sub command 'ecoh'
+
+Did you mean 'echo'?"
+}
+
+@test "subcommands: suggests all commands starting with an unknown name" {
+  fixture "project"
+
+  run main en
+
+  assert_failure
+  assert_output "main: error: no such sub command 'en'
+
+Did you mean one of these?
+    env
+    env-args"
+}
+
+@test "subcommands: suggests close matches for unknown nested commands" {
+  fixture "project"
+
+  run main nested eho
+
+  assert_failure
+  assert_output "main: error: no such sub command 'eho'
+
+Did you mean 'echo'?"
+}
+
+@test "subcommands: writes errors to stderr" {
+  fixture "project"
+
+  run bash -c "main not-found 2>/dev/null"
+
+  assert_failure
+  assert_output ""
+}
+
+@test "subcommands: replaces the sub process with the subcommand" {
+  fixture "project"
+
+  run $SUB_BIN --name main --absolute "$SUB_TEST_DIR/project" -- parent
+
+  assert_success
+  refute_output --partial "sub"
 }
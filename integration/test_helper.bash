@@ -5,6 +5,12 @@ export SUB_ROOT="${BATS_TEST_DIRNAME}/.."
 
 export SUB_TEST_DIR="${BATS_TMPDIR}/sub"
 
+# keeps the machine's plugins, .env files and caches out of the tests, and the tests' files off it
+export XDG_CONFIG_HOME="$SUB_TEST_DIR/xdg/config"
+export XDG_DATA_HOME="$SUB_TEST_DIR/xdg/data"
+export XDG_STATE_HOME="$SUB_TEST_DIR/xdg/state"
+export XDG_CACHE_HOME="$SUB_TEST_DIR/xdg/cache"
+
 if [ -z $SUB_BIN ]; then
   export SUB_BIN=$SUB_ROOT/target/debug/sub
 fi
@@ -0,0 +1,44 @@
+#!/usr/bin/env bats
+
+load test_helper
+
+@test "tree: draws all commands with their summaries" {
+  fixture "project"
+
+  run main --tree directory
+
+  assert_success
+  assert_output "main directory       A directory subcommand
+├── double           Run a double nested command
+│   └── with-help    Help 3
+└── with-help        Help 2"
+}
+
+@test "tree: marks aliases with their target" {
+  fixture "lint"
+
+  run main --tree
+
+  assert_success
+  assert_line "├── alias -> valid             A valid command"
+  assert_line "│   └── invalid-usage          Invalid usage"
+  assert_line "├── no-summary"
+}
+
+@test "tree: prints the full names of all commands with --flat" {
+  fixture "project"
+
+  run main --tree --flat directory
+
+  assert_success
+  assert_output "directory double with-help
+directory with-help"
+}
+
+@test "tree: --flat requires --tree" {
+  fixture "project"
+
+  run main --flat
+
+  assert_failure 64
+}
@@ -46,7 +46,7 @@ Usage: main --usage [commands_with_args]..."
   run main --usage invalid-usage
 
   assert_failure
-  assert_output "main: invalid usage string
+  assert_output "main: error: invalid usage string
   found end of input but expected \"{\""
 }
 
@@ -2,6 +2,7 @@
 load test_helper
 
 PROJECT_DIR="$SUB_TEST_DIR/v1"
+LINT_DIR="$SUB_TEST_DIR/lint"
 
 @test "sub: validates all subcommands in the project directory" {
   fixture "v1"
@@ -9,6 +10,47 @@ PROJECT_DIR="$SUB_TEST_DIR/v1"
   run $SUB_BIN --name main --absolute "$PROJECT_DIR" -- --validate
 
   assert_failure
-  assert_output "$PROJECT_DIR/libexec/invalid-usage: invalid usage string
-  found end of input but expected \"{\""
+  assert_output "$PROJECT_DIR/libexec/invalid-usage: error: invalid usage string
+  found end of input but expected \"{\"
+$PROJECT_DIR/libexec/invalid-usage: error: missing shebang
+$PROJECT_DIR/libexec/invalid-usage: warning: missing summary"
+}
+
+@test "validate: lints the whole libexec tree" {
+  fixture "lint"
+
+  run main --validate
+
+  assert_failure
+  assert_output "$LINT_DIR/libexec/dangling: error: dangling symlink
+$LINT_DIR/libexec/deploy: warning: name conflicts with 'deploy.sh'
+$LINT_DIR/libexec/deploy.sh: warning: name conflicts with 'deploy'
+$LINT_DIR/libexec/loop-a: error: symlink loop
+$LINT_DIR/libexec/loop-b: error: symlink loop
+$LINT_DIR/libexec/missing-env-interpreter: error: interpreter 'missing-interpreter' not found
+$LINT_DIR/libexec/missing-interpreter: error: interpreter '/missing-interpreter' not found
+$LINT_DIR/libexec/nested/invalid-usage: error: invalid usage string
+  found end of input but expected \"{\"
+$LINT_DIR/libexec/no-shebang: error: missing shebang
+$LINT_DIR/libexec/no-summary: warning: missing summary
+$LINT_DIR/libexec/non-exec: warning: non-executable
+$LINT_DIR/libexec/unknown-option: warning: option 'other' does not appear in usage"
+}
+
+@test "validate: succeeds when there are only warnings" {
+  fixture "lint"
+
+  run main --validate no-summary
+
+  assert_success
+  assert_output "$LINT_DIR/libexec/no-summary: warning: missing summary"
+}
+
+@test "validate: succeeds for a valid command" {
+  fixture "lint"
+
+  run main --validate valid
+
+  assert_success
+  assert_output ""
 }
@@ -0,0 +1,397 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::{is_alias, Command};
+use crate::error::Result;
+use crate::parser::{self, Docs};
+use crate::usage::{self, Spec};
+
+/// Version of the index format. Indexes written by other versions are thrown away.
+const INDEX_VERSION: u32 = 1;
+
+/// What sub learns from parsing a script or README.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Metadata {
+    pub docs: Docs,
+    // left out until the full command is needed, and when the usage or options have errors so
+    // they're parsed and reported again
+    pub spec: Option<Spec>,
+}
+
+// a file is parsed again when either of these changes
+#[derive(PartialEq, Eq, Serialize, Deserialize)]
+struct Stamp {
+    size: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+}
+
+impl Stamp {
+    fn of(path: &Path) -> Option<Stamp> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+        Some(Stamp {
+            size: metadata.len(),
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    stamp: Stamp,
+    metadata: Metadata,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Index {
+    version: u32,
+    #[serde(default)]
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Index {
+            version: INDEX_VERSION,
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Mode {
+    Use,
+    Bypass,
+    Rebuild,
+}
+
+/// An index of parsed docs and usage specs by path, so listings, help and completions don't
+/// read and parse every script each time. Entries are checked against the size and modification
+/// time of their file.
+pub struct Cache {
+    path: PathBuf,
+    mode: Cell<Mode>,
+    // loaded the first time a file is looked up
+    index: RefCell<Option<Index>>,
+    // files already parsed again while rebuilding, which don't need to be read a second time
+    rebuilt: RefCell<HashSet<String>>,
+    dirty: Cell<bool>,
+}
+
+impl Cache {
+    pub fn new(path: PathBuf) -> Cache {
+        Cache {
+            path,
+            mode: Cell::new(Mode::Use),
+            index: RefCell::new(None),
+            rebuilt: RefCell::new(HashSet::new()),
+            dirty: Cell::new(false),
+        }
+    }
+
+    /// Parses every file for the rest of the run, without reading or writing the index.
+    pub fn bypass(&self) {
+        self.mode.set(Mode::Bypass);
+    }
+
+    /// Parses every file for the rest of the run and replaces what the index has for it.
+    pub fn rebuild(&self) {
+        self.mode.set(Mode::Rebuild);
+    }
+
+    /// The docs of a file, without parsing its usage and options.
+    pub(crate) fn docs(&self, path: &Path) -> Result<Docs> {
+        self.lookup(path, false).map(|metadata| metadata.docs)
+    }
+
+    /// The docs of a file along with its parsed usage and options. Files that can't be read
+    /// aren't kept in the index, so the error comes back until they're fixed.
+    pub(crate) fn metadata(&self, path: &Path) -> Result<Metadata> {
+        self.lookup(path, true)
+    }
+
+    fn lookup(&self, path: &Path, with_spec: bool) -> Result<Metadata> {
+        let stamp = match self.mode.get() {
+            Mode::Bypass => None,
+            Mode::Use | Mode::Rebuild => Stamp::of(path),
+        };
+
+        let Some(stamp) = stamp else {
+            return read_metadata(path, with_spec);
+        };
+
+        let key = path.to_string_lossy().into_owned();
+
+        let mut index = self.index.borrow_mut();
+        let index = index.get_or_insert_with(|| self.load());
+
+        if self.mode.get() == Mode::Use || self.rebuilt.borrow().contains(&key) {
+            if let Some(entry) = index.entries.get_mut(&key).filter(|entry| entry.stamp == stamp) {
+                if with_spec && entry.metadata.spec.is_none() {
+                    entry.metadata.spec = parse_spec(&entry.metadata.docs);
+
+                    if entry.metadata.spec.is_some() {
+                        self.dirty.set(true);
+                    }
+                }
+
+                return Ok(entry.metadata.clone());
+            }
+        }
+
+        let metadata = read_metadata(path, with_spec)?;
+
+        if self.mode.get() == Mode::Rebuild {
+            self.rebuilt.borrow_mut().insert(key.clone());
+        }
+
+        index.entries.insert(key, Entry { stamp, metadata: metadata.clone() });
+        self.dirty.set(true);
+
+        Ok(metadata)
+    }
+
+    /// Writes the index back if anything was parsed since it was loaded, leaving out files that
+    /// no longer exist. Failing to do so isn't an error, the files are simply parsed again next
+    /// time.
+    pub fn save(&self) {
+        if !self.dirty.replace(false) {
+            return;
+        }
+
+        let mut index = self.index.borrow_mut();
+        let Some(index) = index.as_mut() else {
+            return;
+        };
+
+        index.entries.retain(|path, _| Path::new(path).exists());
+
+        let Ok(contents) = serde_json::to_string(index) else {
+            return;
+        };
+
+        if let Some(directory) = self.path.parent() {
+            let _ = fs::create_dir_all(directory);
+        }
+
+        // renaming keeps other processes from reading a half written index
+        let temporary = self.path.with_extension(format!("{}.tmp", process::id()));
+
+        if fs::write(&temporary, contents).is_err() || fs::rename(&temporary, &self.path).is_err() {
+            let _ = fs::remove_file(&temporary);
+        }
+    }
+
+    // a missing, unreadable or outdated index is the same as an empty one
+    fn load(&self) -> Index {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Index>(&contents).ok())
+            .filter(|index| index.version == INDEX_VERSION)
+            .unwrap_or_default()
+    }
+}
+
+fn read_metadata(path: &Path, with_spec: bool) -> Result<Metadata> {
+    let docs = parser::extract_docs(path)?;
+    let spec = if with_spec { parse_spec(&docs) } else { None };
+
+    Ok(Metadata { docs, spec })
+}
+
+fn parse_spec(docs: &Docs) -> Option<Spec> {
+    let (spec, error) = usage::parse_spec(docs);
+
+    error.is_none().then_some(spec)
+}
+
+/// Loads a command and everything under it, which puts all of them in the index.
+pub fn fill(command: &dyn Command) {
+    for subcommand in command.subcommands() {
+        // commands are parsed the first time their definition is needed
+        subcommand.definition();
+
+        if !is_alias(subcommand.as_ref()) {
+            fill(subcommand.as_ref());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    // a directory of its own for each test, since they run in parallel
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("sub-cache-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn script(path: &Path, summary: &str) {
+        fs::write(path, format!("#!/bin/sh\n# Summary: {}\n", summary)).unwrap();
+    }
+
+    fn set_modified(path: &Path, modified: SystemTime) {
+        fs::File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+    }
+
+    fn summary(cache: &Cache, path: &Path) -> Option<String> {
+        cache.docs(path).ok().and_then(|docs| docs.summary)
+    }
+
+    #[test]
+    fn lookup_reuses_entries_while_size_and_modification_time_match() {
+        let directory = directory("unchanged");
+        let path = directory.join("command");
+        script(&path, "first");
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+        let cache = Cache::new(directory.join("index.json"));
+        assert_eq!(summary(&cache, &path), Some("first".to_owned()));
+
+        // same size and modification time, so the index can't tell
+        script(&path, "other");
+        set_modified(&path, modified);
+
+        assert_eq!(summary(&cache, &path), Some("first".to_owned()));
+    }
+
+    #[test]
+    fn lookup_parses_files_again_when_their_size_changes() {
+        let directory = directory("size");
+        let path = directory.join("command");
+        script(&path, "first");
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+        let cache = Cache::new(directory.join("index.json"));
+        assert_eq!(summary(&cache, &path), Some("first".to_owned()));
+
+        script(&path, "longer");
+        set_modified(&path, modified);
+
+        assert_eq!(summary(&cache, &path), Some("longer".to_owned()));
+    }
+
+    #[test]
+    fn lookup_parses_files_again_when_their_modification_time_changes() {
+        let directory = directory("modified");
+        let path = directory.join("command");
+        script(&path, "first");
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+        let cache = Cache::new(directory.join("index.json"));
+        assert_eq!(summary(&cache, &path), Some("first".to_owned()));
+
+        script(&path, "other");
+        set_modified(&path, modified + Duration::from_secs(1));
+
+        assert_eq!(summary(&cache, &path), Some("other".to_owned()));
+    }
+
+    #[test]
+    fn save_keeps_entries_for_the_next_run_and_drops_missing_files() {
+        let directory = directory("save");
+        let kept = directory.join("kept");
+        let removed = directory.join("removed");
+        script(&kept, "kept");
+        script(&removed, "removed");
+
+        let cache = Cache::new(directory.join("index.json"));
+        assert!(cache.docs(&kept).is_ok());
+        assert!(cache.docs(&removed).is_ok());
+        fs::remove_file(&removed).unwrap();
+        cache.save();
+
+        let index = Cache::new(directory.join("index.json")).load();
+        let paths: Vec<&String> = index.entries.keys().collect();
+
+        assert_eq!(paths, vec![&kept.to_string_lossy().into_owned()]);
+    }
+
+    #[test]
+    fn docs_leave_out_the_spec_until_it_is_needed() {
+        let directory = directory("spec");
+        let path = directory.join("command");
+        script(&path, "first");
+        let key = path.to_string_lossy().into_owned();
+
+        let cache = Cache::new(directory.join("index.json"));
+
+        assert!(cache.docs(&path).is_ok());
+        assert!(cache.index.borrow().as_ref().unwrap().entries[&key].metadata.spec.is_none());
+
+        assert!(cache.metadata(&path).is_ok());
+        assert!(cache.index.borrow().as_ref().unwrap().entries[&key].metadata.spec.is_some());
+    }
+
+    #[test]
+    fn rebuild_replaces_what_the_index_has() {
+        let directory = directory("rebuild");
+        let path = directory.join("command");
+        script(&path, "first");
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+        let cache = Cache::new(directory.join("index.json"));
+        assert!(cache.docs(&path).is_ok());
+        cache.save();
+
+        script(&path, "other");
+        set_modified(&path, modified);
+
+        let cache = Cache::new(directory.join("index.json"));
+        cache.rebuild();
+
+        assert_eq!(summary(&cache, &path), Some("other".to_owned()));
+    }
+
+    #[test]
+    fn rebuild_reads_each_file_once() {
+        let directory = directory("rebuild-once");
+        let path = directory.join("command");
+        script(&path, "first");
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+        let cache = Cache::new(directory.join("index.json"));
+        cache.rebuild();
+        assert_eq!(summary(&cache, &path), Some("first".to_owned()));
+
+        // a second read would see the new summary
+        script(&path, "other");
+        set_modified(&path, modified);
+
+        let metadata = cache.metadata(&path).ok().map(|metadata| (metadata.docs.summary, metadata.spec.is_some()));
+        assert_eq!(metadata, Some((Some("first".to_owned()), true)));
+    }
+
+    #[test]
+    fn bypass_ignores_the_index() {
+        let directory = directory("bypass");
+        let path = directory.join("command");
+        script(&path, "first");
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+        let cache = Cache::new(directory.join("index.json"));
+        assert!(cache.docs(&path).is_ok());
+        cache.save();
+
+        script(&path, "other");
+        set_modified(&path, modified);
+
+        let cache = Cache::new(directory.join("index.json"));
+        cache.bypass();
+
+        assert_eq!(summary(&cache, &path), Some("other".to_owned()));
+    }
+}
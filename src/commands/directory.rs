@@ -1,77 +1,74 @@
+use std::cell::OnceCell;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::collections::HashMap;
 
 use clap::Arg;
 
-use crate::commands::subcommand;
-use crate::commands::Command;
-use crate::config::Config;
-use crate::error::{Error, Result};
-use crate::parser;
+use crate::commands::{display_entries, entry_command, listed_name};
+use crate::commands::{Command, CompletionType};
+use crate::config::{Config, DefaultHelp};
+use crate::error::{Error, Result, Severity};
+use crate::lint;
 use crate::usage::Usage;
 
 pub struct DirectoryCommand<'a> {
     names: Vec<String>,
-    path: PathBuf,
-    usage: Usage,
+    paths: Vec<PathBuf>,
+    // the definition before the README is read
+    command: clap::Command,
+    usage: OnceCell<Usage>,
     config: &'a Config,
 }
 
 impl<'a> DirectoryCommand<'a> {
-    pub fn top_level(names: Vec<String>, path: PathBuf, config: &'a Config) -> Self {
-        let readme_path = path.join("README");
-
-        let mut command = config.user_cli_command(&config.name);
-
-        if readme_path.exists() {
-            let docs = parser::extract_docs(&readme_path);
-
-            if let Some(summary) = docs.summary {
-                command = command.about(summary);
-            }
-
-            if let Some(description) = docs.description {
-                command = command.after_help(description);
-            }
+    pub fn top_level(names: Vec<String>, paths: Vec<PathBuf>, config: &'a Config) -> Self {
+        Self {
+            names,
+            paths,
+            command: config.user_cli_command(&config.name),
+            usage: OnceCell::new(),
+            config,
         }
+    }
 
-        let usage = Usage::new(command, HashMap::new(), None);
+    pub fn new(name: &str, names: Vec<String>, paths: Vec<PathBuf>, config: &'a Config) -> Self {
+        let mut command = config.base_command(name);
+        command = command.arg(Arg::new("commands_with_args").trailing_var_arg(true).allow_hyphen_values(true).num_args(..));
 
         Self {
             names,
-            path,
-            usage,
+            paths,
+            command,
+            usage: OnceCell::new(),
             config,
         }
     }
 
-    pub fn new(name: &str, names: Vec<String>, path: PathBuf, config: &'a Config) -> Self {
-        let readme_path = path.join("README");
+    fn parsed(&self) -> &Usage {
+        self.usage.get_or_init(|| {
+            let mut command = self.command.clone();
 
-        let mut command = config.base_command(name);
-        command = command.arg(Arg::new("commands_with_args").trailing_var_arg(true).allow_hyphen_values(true).num_args(..));
+            // an unreadable README is reported by --validate
+            if let Some(docs) = self.readme().and_then(|path| self.config.cache.docs(&path).ok()) {
 
-        if readme_path.exists() {
-            let docs = parser::extract_docs(&readme_path);
-
-            if let Some(summary) = docs.summary {
-                command = command.about(summary);
-            }
+                if let Some(summary) = docs.summary {
+                    command = command.about(summary);
+                }
 
-            if let Some(description) = docs.description {
-                command = command.after_help(description);
+                if let Some(description) = docs.description {
+                    command = command.after_help(description);
+                }
             }
-        }
 
-        let usage = Usage::new(command, HashMap::new(), None);
+            Usage::new(command, HashMap::new(), Vec::new(), false, None)
+        })
+    }
 
-        Self {
-            names,
-            path,
-            usage,
-            config,
-        }
+    // the README of the first merged directory that has one documents the group
+    fn readme(&self) -> Option<PathBuf> {
+        self.paths.iter().map(|path| path.join("README")).find(|path| path.is_file())
     }
 }
 
@@ -80,34 +77,45 @@ impl<'a> Command for DirectoryCommand<'a> {
         self.names.last().unwrap()
     }
 
+    fn names(&self) -> &[String] {
+        &self.names
+    }
+
     fn summary(&self) -> String {
-        self.usage.summary()
+        match self.usage.get() {
+            Some(usage) => usage.summary(),
+            None => self.readme().and_then(|path| self.config.cache.docs(&path).ok()).and_then(|docs| docs.summary).unwrap_or_default(),
+        }
     }
 
     fn usage(&self) -> Result<String> {
-        Ok(self.usage.generate().to_string())
+        Ok(self.parsed().generate().to_string())
     }
 
     fn help(&self) -> Result<String> {
-        let mut help = self.usage.help()?;
+        let mut help = self.parsed().help()?;
 
         let subcommands = self.subcommands();
         if !subcommands.is_empty() {
             help.push_str("\nAvailable subcommands:\n");
 
-            let max_width = subcommands
+            let names: Vec<String> = subcommands
+                .iter()
+                .map(|subcommand| listed_name(self.config, subcommand.as_ref()))
+                .collect();
+
+            let max_width = names
                 .iter()
-                .map(|subcommand| subcommand.name())
                 .map(|name| name.len())
                 .max()
                 .unwrap();
 
             let width = max_width + 4;
 
-            for subcommand in subcommands {
+            for (name, subcommand) in names.iter().zip(subcommands) {
                 help.push_str(&format!(
                     "    {:width$}{}\n",
-                    subcommand.name(),
+                    name,
                     subcommand.summary(),
                     width = width
                 ));
@@ -118,21 +126,14 @@ impl<'a> Command for DirectoryCommand<'a> {
     }
 
     fn subcommands(&self) -> Vec<Box<dyn Command + '_>> {
-        let mut libexec_path = self.config.libexec_path();
-        libexec_path.extend(&self.names);
-
         let mut subcommands = Vec::new();
 
-        if libexec_path.is_dir() {
-            for entry in fs::read_dir(libexec_path).unwrap() {
-                let name = entry.unwrap().file_name().to_str().unwrap().to_owned();
+        for (name, path) in display_entries(self.config, &self.paths) {
+            let mut names = self.names.clone();
+            names.push(name);
 
-                let mut names = self.names.clone();
-                names.push(name);
-
-                if let Ok(subcommand) = subcommand(self.config, names) {
-                    subcommands.push(subcommand);
-                }
+            if let Ok(subcommand) = entry_command(self.config, names, &self.paths, path, Vec::new()) {
+                subcommands.push(subcommand);
             }
         }
 
@@ -150,28 +151,128 @@ impl<'a> Command for DirectoryCommand<'a> {
     }
 
     fn invoke(&self) -> Result<i32> {
-        if !self.path.exists() {
+        if self.paths.is_empty() {
             return Err(Error::UnknownSubCommand(
                 self.names.last().unwrap().to_owned(),
+                Vec::new(),
             ));
         }
 
-        println!("{}", self.help()?);
+        match self.config.default_help {
+            DefaultHelp::Full => println!("{}", self.help()?),
+            DefaultHelp::Usage => println!("{}", self.usage()?),
+            DefaultHelp::Commands => {
+                for subcommand in self.subcommands() {
+                    println!("{}", listed_name(self.config, subcommand.as_ref()));
+                }
+            }
+        }
 
         Ok(0)
     }
 
-    fn validate(&self) -> Vec<(PathBuf, Error)> {
+    fn validate(&self) -> Vec<(PathBuf, Severity, Error)> {
         let mut errors = Vec::new();
+        let mut aliases = Vec::new();
+        let mut owners = HashMap::<String, PathBuf>::new();
+        let mut stems = HashMap::<String, Vec<(String, PathBuf)>>::new();
+
+        if let Some(readme_path) = self.readme() {
+            if let Err(e) = self.config.cache.docs(&readme_path) {
+                errors.push((readme_path, Severity::Error, e));
+            }
+        }
+
+        for directory in &self.paths {
+            let entries = match fs::read_dir(directory) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    errors.push((directory.clone(), Severity::Error, Error::SubCommandIoError(Rc::new(e))));
+                    continue;
+                }
+            };
+
+            for entry in entries.flatten() {
+                let path = entry.path();
+
+                // names that aren't UTF-8 can't be typed or listed, so they're left out
+                let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+                    errors.push((path, Severity::Warning, Error::InvalidUTF8));
+                    continue;
+                };
+
+                if name.starts_with('.') || name == "README" {
+                    continue;
+                }
+
+                if let Some(error) = lint::check_symlink(&path) {
+                    errors.push((path, Severity::Error, error));
+                    continue;
+                }
+
+                // same-named directories are merged, anything else is hidden by the first one
+                if let Some(owner) = owners.get(&name) {
+                    if !(owner.is_dir() && path.is_dir()) {
+                        errors.push((path, Severity::Warning, Error::Shadowed(owner.clone())));
+                    }
+                    continue;
+                }
+
+                owners.insert(name.clone(), path.clone());
+
+                // aliases are validated through their targets
+                if entry.file_type().map(|t| t.is_symlink()).unwrap_or(false) {
+                    aliases.push(name.clone());
+                }
+
+                if path.is_file() && !lint::is_executable(&path) && self.config.interpreter_for(&path).is_none() {
+                    errors.push((path, Severity::Warning, Error::NonExecutable(name)));
+                    continue;
+                }
+
+                let stem = Path::new(&name).file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| name.clone());
+                stems.entry(stem).or_default().push((name, path));
+            }
+        }
+
+        for entries in stems.values().filter(|entries| entries.len() > 1) {
+            for (name, path) in entries {
+                let mut others: Vec<String> = entries.iter().map(|(other, _)| other).filter(|other| *other != name).cloned().collect();
+                others.sort();
+                errors.push((path.clone(), Severity::Warning, Error::DuplicateName(others)));
+            }
+        }
 
         for subcommand in self.subcommands() {
+            if aliases.iter().any(|alias| alias == subcommand.name()) {
+                continue;
+            }
+
             errors.extend(subcommand.validate());
         }
 
+        errors.sort_by(|e1, e2| e1.0.cmp(&e2.0));
+
         errors
     }
 
     fn path(&self) -> Option<PathBuf> {
         None
     }
+
+    fn origins(&self) -> Vec<PathBuf> {
+        self.paths.clone()
+    }
+
+    fn definition(&self) -> &clap::Command {
+        self.parsed().command()
+    }
+
+    fn completion_type(&self, _argument: &str) -> Option<CompletionType> {
+        None
+    }
+
+    fn is_eval(&self) -> bool {
+        false
+    }
 }
@@ -1,36 +1,107 @@
+use std::cell::OnceCell;
+use std::env;
+use std::fs;
 use std::path::PathBuf;
 use std::process;
+use std::rc::Rc;
+use std::os::unix::process::CommandExt;
 
 use crate::config::Config;
 use crate::usage::{self, Usage};
 use crate::parser;
-use crate::error::{Error, Result};
-use crate::commands::Command;
+use crate::error::{Error, Result, Severity};
+use crate::lint;
+use crate::spawn;
+use crate::dotenv;
+use crate::commands::{Command, CompletionType};
 
 pub struct FileCommand<'a> {
     names: Vec<String>,
     path: PathBuf,
-    usage: Usage,
+    // parsed the first time more than the summary is needed
+    usage: OnceCell<Usage>,
     args: Vec<String>,
+    interpreter: Option<Vec<String>>,
     config: &'a Config,
 }
 
 impl<'a> FileCommand<'a> {
     pub fn new(names: Vec<String>, path: PathBuf, args: Vec<String>, config: &'a Config) -> Self {
-        let mut cmd = vec![config.name.to_owned()];
-        cmd.extend(names.iter().map(|s| s.to_owned()));
-        let cmd = cmd.join(" ");
-
-        let usage = usage::extract_usage(config, &path, &cmd);
+        let interpreter = if lint::is_executable(&path) {
+            None
+        } else {
+            config.interpreter_for(&path)
+        };
 
         Self {
             names,
             path,
-            usage,
+            usage: OnceCell::new(),
             args,
+            interpreter,
             config,
         }
     }
+
+    fn parsed(&self) -> &Usage {
+        self.usage.get_or_init(|| {
+            let mut cmd = vec![self.config.name.to_owned()];
+            cmd.extend(self.names.iter().map(|s| s.to_owned()));
+            let cmd = cmd.join(" ");
+
+            usage::extract_usage(self.config, &self.path, &cmd)
+        })
+    }
+
+    fn command(&self) -> process::Command {
+        let mut command = match &self.interpreter {
+            Some(interpreter) => {
+                let mut command = process::Command::new(&interpreter[0]);
+                command.args(&interpreter[1..]).arg(&self.path);
+                command
+            }
+            None => process::Command::new(&self.path),
+        };
+
+        // variables sub was started with win over `[env]`, just like over `.env` files
+        command.envs(self.config.env.iter().filter(|(key, _)| env::var_os(key).is_none()));
+
+        command
+    }
+
+    // variables describing the CLI, its directories and this command, so scripts can call back
+    // into the CLI and keep files in the right places
+    fn set_cli_env(&self, command: &mut process::Command) -> Result<()> {
+        // sub can't know whether a script will write to these, so they're created whenever one
+        // runs rather than on first use; listings and help never get here, and creating
+        // directories that already exist is a single cheap syscall each
+        self.config.create_directories()?;
+
+        // variables sub was started with win, so `FOO=bar hat cmd` does what it looks like
+        for path in self.config.dotenv_paths() {
+            command.envs(dotenv::load(&path)?.into_iter().filter(|(key, _)| env::var_os(key).is_none()));
+        }
+
+        let prefix = format!("_{}", self.config.name.to_uppercase());
+
+        let mut command_path = vec![self.config.name.to_owned()];
+        command_path.extend(self.names.iter().cloned());
+
+        command.env(format!("{}_ROOT", prefix), &self.config.root);
+        command.env(format!("{}_CACHE", prefix), &self.config.cache_directory);
+        command.env(format!("{}_CONFIG", prefix), &self.config.config_directory);
+        command.env(format!("{}_DATA", prefix), &self.config.data_directory);
+        command.env(format!("{}_STATE", prefix), &self.config.state_directory);
+        command.env(format!("{}_NAME", prefix), &self.config.name);
+        command.env(format!("{}_COMMAND", prefix), command_path.join(" "));
+        command.env(format!("{}_COMMAND_NAME", prefix), self.name());
+
+        if let Some(executable) = &self.config.executable {
+            command.env(format!("{}_EXECUTABLE", prefix), executable);
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a> Command for FileCommand<'a> {
@@ -38,20 +109,27 @@ impl<'a> Command for FileCommand<'a> {
         self.names.last().unwrap()
     }
 
+    fn names(&self) -> &[String] {
+        &self.names
+    }
+
     fn summary(&self) -> String {
-        self.usage.summary()
+        match self.usage.get() {
+            Some(usage) => usage.summary(),
+            None => self.config.cache.docs(&self.path).ok().and_then(|docs| docs.summary).unwrap_or_default(),
+        }
     }
 
     fn usage(&self) -> Result<String> {
-        self.usage.validate()?;
+        self.parsed().validate()?;
 
-        Ok(self.usage.generate().to_string())
+        Ok(self.parsed().generate().to_string())
     }
 
     fn help(&self) -> Result<String> {
-        self.usage.validate()?;
+        self.parsed().validate()?;
 
-        self.usage.help()
+        self.parsed().help()
     }
 
     fn subcommands(&self) -> Vec<Box<dyn Command + '_>> {
@@ -60,39 +138,29 @@ impl<'a> Command for FileCommand<'a> {
 
     fn completions(&self) -> Result<i32> {
         // new completion system
-        if self.usage.provides_completions() {
-            let name = self.usage.get_next_option_name_for_completions(&self.args);
+        if self.parsed().provides_completions() {
+            let name = self.parsed().get_next_option_name_for_completions(&self.args);
 
             let completion_type = match name {
-                Some(ref name) => self.usage.get_completion_type(name),
+                Some(ref name) => self.parsed().get_completion_type(name),
                 None => None,
             };
 
             match completion_type {
                 Some(usage::CompletionType::Script) => {
-                    let mut command = process::Command::new(&self.path);
+                    let mut command = self.command();
 
-                    command.env(format!("_{}_ROOT", self.config.name.to_uppercase()), &self.config.root);
+                    self.set_cli_env(&mut command)?;
                     command.env(format!("_{}_COMPLETE", self.config.name.to_uppercase()), "true");
                     command.env(format!("_{}_COMPLETE_ARG", self.config.name.to_uppercase()), name.unwrap());
 
-                    let status = command.status().unwrap();
-
-                    return match status.code() {
-                        Some(code) => Ok(code),
-                        None => Err(Error::SubCommandInterrupted),
-                    };
+                    return spawn::status(&mut command);
                 },
                 Some(usage::CompletionType::LiteralCommand(cmd)) => {
                     let mut command = process::Command::new("/bin/sh");
                     command.arg("-c").arg(&cmd);
 
-                    let status = command.status().unwrap();
-
-                    return match status.code() {
-                        Some(code) => Ok(code),
-                        None => Err(Error::SubCommandInterrupted),
-                    };
+                    return spawn::status(&mut command);
                 },
                 None => {
                     // do nothing
@@ -103,57 +171,110 @@ impl<'a> Command for FileCommand<'a> {
         }
 
         // old completion system
-        if parser::provides_completions(&self.path) {
-            let mut command = process::Command::new(&self.path);
+        if parser::provides_completions(&self.path)? {
+            let mut command = self.command();
 
             command.arg("--complete");
-            command.env(format!("_{}_ROOT", self.config.name.to_uppercase()), &self.config.root);
+            self.set_cli_env(&mut command)?;
 
-            let status = command.status().unwrap();
-
-            return match status.code() {
-                Some(code) => Ok(code),
-                None => Err(Error::SubCommandInterrupted),
-            };
+            return spawn::status(&mut command);
         }
 
         Ok(0)
     }
 
     fn invoke(&self) -> Result<i32> {
-        self.usage.validate()?;
+        self.parsed().validate()?;
 
         if !self.path.exists() {
-            return Err(Error::UnknownSubCommand(self.names.last().unwrap().to_owned()));
+            return Err(Error::UnknownSubCommand(self.names.last().unwrap().to_owned(), Vec::new()));
         }
 
-        let mut command = process::Command::new(&self.path);
+        let mut command = self.command();
 
         command.args(&self.args);
 
-        command.env(format!("_{}_ROOT", self.config.name.to_uppercase()), &self.config.root);
-        command.env(format!("_{}_CACHE", self.config.name.to_uppercase()), &self.config.cache_directory);
-        command.env(format!("_{}_ARGS", self.config.name.to_uppercase()), &self.usage.parse_into_kv(&self.args)?);
+        command.env(format!("_{}_ARGS", self.config.name.to_uppercase()), &self.parsed().parse_into_kv(&self.args)?);
+        self.set_cli_env(&mut command)?;
 
-        let status = match command.status() {
-            Ok(status) => status,
-            Err(e) => return Err(Error::SubCommandIoError(std::rc::Rc::new(e))),
-        };
+        // exec falls back to running files without a shebang through /bin/sh, which would
+        // happily "succeed" on empty or binary garbage files
+        if self.interpreter.is_none() {
+            if let Some(Error::MissingShebang) = lint::check_shebang(&self.path) {
+                return Err(Error::MissingShebang);
+            }
+        }
+
+        // the --init wrapper function evaluates whatever eval commands print, everything else keeps
+        // the terminal; the variable is dropped so nested calls don't write into the same file
+        let eval_variable = format!("_{}_EVAL_FILE", self.config.name.to_uppercase());
+        if let Some(eval_file) = env::var_os(&eval_variable) {
+            command.env_remove(&eval_variable);
 
-        match status.code() {
-            Some(code) => Ok(code),
-            None => Err(Error::SubCommandInterrupted),
+            if self.parsed().is_eval() {
+                let file = fs::File::create(eval_file).map_err(|e| Error::SubCommandIoError(Rc::new(e)))?;
+                command.stdout(file);
+            }
         }
+
+        // exec doesn't return to write the index
+        self.config.cache.save();
+
+        // replace the sub process so signals, pids and exit statuses belong to the script itself;
+        // exec only returns if the script couldn't be started
+        let e = command.exec();
+
+        Err(Error::SubCommandIoError(Rc::new(e)))
     }
 
-    fn validate(&self) -> Vec<(PathBuf, Error)> {
-        match self.usage.validate() {
-            Ok(_) => Vec::new(),
-            Err(e) => vec![(self.path.clone(), e)],
+    fn validate(&self) -> Vec<(PathBuf, Severity, Error)> {
+        let mut errors = Vec::new();
+
+        if let Err(e) = self.parsed().validate() {
+            errors.push((self.path.clone(), Severity::Error, e));
+        }
+
+        match &self.interpreter {
+            Some(interpreter) => {
+                if lint::find_in_path(&interpreter[0]).is_none() {
+                    errors.push((self.path.clone(), Severity::Error, Error::InterpreterNotFound(interpreter[0].to_owned())));
+                }
+            }
+            None => {
+                if let Some(e) = lint::check_shebang(&self.path) {
+                    errors.push((self.path.clone(), Severity::Error, e));
+                }
+            }
+        }
+
+        for name in self.parsed().unknown_options() {
+            errors.push((self.path.clone(), Severity::Warning, Error::UnknownOption(name.to_owned())));
+        }
+
+        if self.parsed().summary().is_empty() {
+            errors.push((self.path.clone(), Severity::Warning, Error::MissingSummary));
         }
+
+        errors
     }
 
     fn path(&self) -> Option<PathBuf> {
         Some(self.path.clone())
     }
+
+    fn origins(&self) -> Vec<PathBuf> {
+        vec![self.path.clone()]
+    }
+
+    fn definition(&self) -> &clap::Command {
+        self.parsed().command()
+    }
+
+    fn completion_type(&self, argument: &str) -> Option<CompletionType> {
+        self.parsed().get_completion_type(argument)
+    }
+
+    fn is_eval(&self) -> bool {
+        self.parsed().is_eval()
+    }
 }
@@ -1,79 +1,336 @@
 pub mod file;
 pub mod directory;
 
-use std::path::PathBuf;
-use std::os::unix::fs::PermissionsExt;
+use std::fs;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 use crate::config::Config;
 use crate::commands::file::FileCommand;
 use crate::commands::directory::DirectoryCommand;
 use crate::error::Result;
 use crate::error::Error;
+use crate::error::Severity;
+use crate::lint;
 
+pub use crate::usage::CompletionType;
+
+/// A command or group of commands. Creating one doesn't read its docs: `name`, `summary`,
+/// `path` and `origins` only read what listings need, while everything else parses the full
+/// docs and usage the first time it's called.
 pub trait Command {
     fn name(&self) -> &str;
+    /// The names leading to the command from the CLI itself, which has none.
+    fn names(&self) -> &[String];
+    /// The summary line of the command's docs, read without parsing the rest.
     fn summary(&self) -> String;
     fn usage(&self) -> Result<String>;
     fn subcommands(&self) -> Vec<Box<dyn Command + '_>>;
     fn completions(&self) -> Result<i32>;
     fn invoke(&self) -> Result<i32>;
     fn help(&self) -> Result<String>;
-    fn validate(&self) -> Vec<(PathBuf, Error)>;
+    fn validate(&self) -> Vec<(PathBuf, Severity, Error)>;
     fn path(&self) -> Option<PathBuf>;
+    /// Where the command comes from: its script, or every directory merged into a group.
+    fn origins(&self) -> Vec<PathBuf>;
+    /// The clap definition built from the command's docs: summary, arguments and description.
+    fn definition(&self) -> &clap::Command;
+    /// How the value of an argument is completed, if the docs say so.
+    fn completion_type(&self, argument: &str) -> Option<CompletionType>;
+    /// Whether the command prints shell code for the `--init` wrapper function to evaluate.
+    fn is_eval(&self) -> bool;
 }
 
 pub fn subcommand(config: &Config, mut cliargs: Vec<String>) -> Result<Box<dyn Command + '_>> {
-    let mut path = config.libexec_path();
+    let mut dirs = config.libexec_paths();
     let mut names = Vec::new();
 
     if cliargs.is_empty() {
-        if path.is_dir() {
-            return Ok(Box::new(DirectoryCommand::top_level(names, path, config)));
-        } else {
+        // the other directories only add to the commands in root, which has to exist
+        if !config.root.join("libexec").is_dir() {
             return Err(Error::NoLibexecDir);
+        } else {
+            return Ok(Box::new(DirectoryCommand::top_level(names, dirs, config)));
         }
     }
 
     loop {
-        let head = cliargs[0].clone();
+        let mut head = cliargs[0].clone();
 
         if head.starts_with('.') {
-            return Err(Error::UnknownSubCommand(head.to_owned()));
+            return Err(Error::UnknownSubCommand(head.to_owned(), Vec::new()));
         }
 
-        path.push(&head);
-
-        if !path.exists() {
-            return Err(Error::UnknownSubCommand(head));
+        if resolve_entry(config, &dirs, &head)?.is_none() {
+            if let Some(name) = inferred_name(config, &dirs, &head)? {
+                head = name;
+            } else if let Some(name) = autocorrection(config, &dirs, &head) {
+                config.report(Severity::Warning, &format!("no such sub command '{}', running '{}'", head, name));
+                head = name;
+            } else {
+                return Err(Error::UnknownSubCommand(head.clone(), suggestions(config, &dirs, &head)));
+            }
         }
 
+        // the path on disk, whose file name differs from head when the extension was left out;
+        // inferred and autocorrected names come from the listing, so they always resolve
+        let path = match resolve_entry(config, &dirs, &head)? {
+            Some(path) => path,
+            None => return Err(Error::UnknownSubCommand(head.clone(), Vec::new())),
+        };
+
         names.push(head.to_owned());
 
         cliargs = cliargs.split_off(1);
 
-        if cliargs.is_empty() {
-            if path.is_dir() {
-                let mut name_parts = vec![config.name.to_owned()];
-                name_parts.append(&mut names.clone());
-                return Ok(Box::new(DirectoryCommand::new(&name_parts.join(" "), names, path, config)));
+        if path.is_dir() && !cliargs.is_empty() {
+            dirs = merged_dirs(&dirs, &path);
+            continue;
+        }
+
+        return entry_command(config, names, &dirs, path, cliargs);
+    }
+}
+
+/// The command for an entry that was already found in the command directories, so listings
+/// don't look every name up again.
+pub(crate) fn entry_command<'a>(config: &'a Config, names: Vec<String>, dirs: &[PathBuf], path: PathBuf, cliargs: Vec<String>) -> Result<Box<dyn Command + 'a>> {
+    if path.is_dir() {
+        let mut name_parts = vec![config.name.to_owned()];
+        name_parts.extend(names.iter().cloned());
+
+        return Ok(Box::new(DirectoryCommand::new(&name_parts.join(" "), names, merged_dirs(dirs, &path), config)));
+    }
+
+    if !is_runnable(config, &path) {
+        return Err(Error::NonExecutable(names.last().cloned().unwrap_or_default()));
+    }
+
+    Ok(Box::new(FileCommand::new(names, path, cliargs, config)))
+}
+
+// directories with the same name further down the search path are merged into this one; the
+// first directory that has it always comes first
+fn merged_dirs(dirs: &[PathBuf], path: &Path) -> Vec<PathBuf> {
+    let entry = path.file_name().unwrap_or_default();
+
+    dirs.iter().map(|dir| dir.join(entry)).filter(|dir| dir.is_dir()).collect()
+}
+
+/// The name of a command as listed in help and by `--commands`, marking commands that come from
+/// the project around the current directory.
+pub fn listed_name(config: &Config, command: &dyn Command) -> String {
+    let origins = command.origins();
+
+    if !origins.is_empty() && origins.iter().all(|path| config.is_project_local(path)) {
+        format!("{} (project)", command.name())
+    } else {
+        command.name().to_owned()
+    }
+}
+
+/// Whether a command was reached through a symlink in a command directory. The CLI itself is
+/// never an alias, even when its command directories are symlinks.
+pub fn is_alias(command: &dyn Command) -> bool {
+    !command.names().is_empty() && command.origins().first().is_some_and(|path| path.is_symlink())
+}
+
+/// Where an alias points to, as written in the symlink.
+pub fn alias_target(command: &dyn Command) -> Option<PathBuf> {
+    if is_alias(command) {
+        fs::read_link(command.origins().first()?).ok()
+    } else {
+        None
+    }
+}
+
+fn is_runnable(config: &Config, path: &Path) -> bool {
+    lint::is_executable(path) || config.interpreter_for(path).is_some()
+}
+
+/// The entries of a list of command directories merged by name, sorted. The first directory that
+/// has an entry shadows that name in every later one, even when the entry itself can't be run.
+fn merged_entries(config: &Config, dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
+
+    for dir in dirs {
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                // reported by --validate
+                let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+                    continue;
+                };
+                let entry_path = entry.path();
+
+                if name.starts_with('.') || !entry_path.exists() || !seen.insert(name.clone()) {
+                    continue;
+                }
+
+                if entry_path.is_dir() || (entry_path.is_file() && is_runnable(config, &entry_path)) {
+                    merged.push((name, entry_path));
+                }
             }
+        }
+    }
+
+    merged.sort();
+
+    merged
+}
+
+/// Names of the entries in the command directories as they are listed and completed: files lose
+/// their extension unless that would make them collide with another entry.
+fn display_names(config: &Config, dirs: &[PathBuf]) -> Vec<String> {
+    display_entries(config, dirs).into_iter().map(|(name, _)| name).collect()
+}
+
+/// The entries of the command directories by their listed name, sorted by it.
+pub(crate) fn display_entries(config: &Config, dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let entries = merged_entries(config, dirs);
+
+    let stems: Vec<Option<String>> = entries.iter().map(|(_, path)| file_stem(path)).collect();
+
+    let mut named: Vec<(String, PathBuf)> = entries
+        .iter()
+        .zip(&stems)
+        .map(|((entry, path), stem)| match stem {
+            Some(stem) if stem != entry
+                && !entries.iter().any(|(other, _)| other == stem)
+                && stems.iter().filter(|other| other.as_ref() == Some(stem)).count() == 1 => (stem.clone(), path.clone()),
+            _ => (entry.clone(), path.clone()),
+        })
+        .collect();
+
+    named.sort();
 
-            if path.metadata().unwrap().permissions().mode() & 0o111 == 0 {
-                return Err(Error::NonExecutable(head.to_owned()));
+    named
+}
+
+fn file_stem(path: &Path) -> Option<String> {
+    if path.is_file() {
+        path.file_stem().map(|stem| stem.to_string_lossy().into_owned())
+    } else {
+        None
+    }
+}
+
+fn resolve_entry(config: &Config, dirs: &[PathBuf], name: &str) -> Result<Option<PathBuf>> {
+    if let Some(path) = dirs.iter().map(|dir| dir.join(name)).find(|path| path.exists()) {
+        return Ok(Some(path));
+    }
+
+    let mut candidates: Vec<(String, PathBuf)> = merged_entries(config, dirs)
+        .into_iter()
+        .filter(|(_, path)| file_stem(path).is_some_and(|stem| stem == name))
+        .collect();
+
+    match candidates.len() {
+        0 => Ok(None),
+        1 => Ok(candidates.pop().map(|(_, path)| path)),
+        _ => Err(Error::AmbiguousSubCommand(name.to_owned(), candidates.into_iter().map(|(entry, _)| entry).collect())),
+    }
+}
+
+fn prefix_matches(config: &Config, dirs: &[PathBuf], name: &str) -> Vec<String> {
+    // a dangling symlink exists as an entry, so it must not be replaced by a sibling
+    if dirs.iter().any(|dir| fs::symlink_metadata(dir.join(name)).is_ok()) {
+        return Vec::new();
+    }
+
+    display_names(config, dirs).into_iter().filter(|candidate| candidate.starts_with(name)).collect()
+}
+
+fn inferred_name(config: &Config, dirs: &[PathBuf], name: &str) -> Result<Option<String>> {
+    if !config.infer_subcommands {
+        return Ok(None);
+    }
+
+    let mut candidates = prefix_matches(config, dirs, name);
+
+    match candidates.len() {
+        0 => Ok(None),
+        1 => Ok(candidates.pop()),
+        _ => Err(Error::AmbiguousSubCommand(name.to_owned(), candidates)),
+    }
+}
+
+fn autocorrection(config: &Config, dirs: &[PathBuf], name: &str) -> Option<String> {
+    if !config.autocorrect {
+        return None;
+    }
+
+    let mut candidates = prefix_matches(config, dirs, name);
+
+    if candidates.len() == 1 {
+        candidates.pop()
+    } else {
+        None
+    }
+}
+
+fn suggestions(config: &Config, dirs: &[PathBuf], name: &str) -> Vec<String> {
+    // allow roughly one typo for every three characters
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    let mut candidates: Vec<(usize, String)> = display_names(config, dirs)
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(name, &candidate);
+
+            if candidate.starts_with(name) || distance <= max_distance {
+                Some((distance, candidate))
+            } else {
+                None
             }
+        })
+        .collect();
 
-            return Ok(Box::new(FileCommand::new(names, path, cliargs, config)));
-        }
+    candidates.sort();
 
-        if path.is_dir() {
-            continue;
-        }
+    candidates.into_iter().map(|(_, candidate)| candidate).collect()
+}
 
-        if path.metadata().unwrap().permissions().mode() & 0o111 == 0 {
-            return Err(Error::NonExecutable(head.to_owned()));
-        }
+// optimal string alignment distance, so swapped adjacent characters count as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
 
-        return Ok(Box::new(FileCommand::new(names, path, cliargs, config)));
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
     }
+
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_insertions_deletions_substitutions_and_transpositions() {
+        assert_eq!(edit_distance("deploy", "deploy"), 0);
+        assert_eq!(edit_distance("deplyo", "deploy"), 1);
+        assert_eq!(edit_distance("deply", "deploy"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -1,10 +1,25 @@
+use std::env;
+use std::fs;
+use std::rc::Rc;
+use std::io::{self, IsTerminal};
 use std::process::exit;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, HashMap};
 
 use clap::{Command, ColorChoice, Arg, ArgGroup, ValueEnum};
-use clap::builder::Styles;
+use clap::builder::{EnumValueParser, Styles};
+use clap::builder::styling::AnsiColor;
+use serde::Deserialize;
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+use crate::cache::Cache;
+use crate::error::{Error, Result, Severity, EXIT_FAILURE};
+use crate::listing::Format;
+use crate::shell::Shell;
+
+pub const CONFIG_FILE_NAME: &str = "sub.toml";
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Color {
     /// Enables colored output only when the output is going to a terminal or TTY.
     Auto,
@@ -14,47 +29,203 @@ pub enum Color {
     Never,
 }
 
+/// What invoking a directory without a subcommand prints.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultHelp {
+    /// The full help, including the list of subcommands.
+    #[default]
+    Full,
+    /// Only the usage line.
+    Usage,
+    /// Only the names of the subcommands, like `--commands`.
+    Commands,
+}
+
+/// Settings read from `sub.toml` in the CLI root. Command line flags take precedence.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Settings {
+    pub name: Option<String>,
+    pub color: Option<Color>,
+    pub infer_long_arguments: Option<bool>,
+    pub infer_subcommands: Option<bool>,
+    pub autocorrect: Option<bool>,
+    pub default_help: Option<DefaultHelp>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub interpreters: HashMap<String, String>,
+    #[serde(default)]
+    pub libexec_paths: Vec<PathBuf>,
+    pub dotenv_current_dir: Option<bool>,
+}
+
+impl Settings {
+    pub fn load(root: &Path) -> Result<Settings> {
+        let path = root.join(CONFIG_FILE_NAME);
+
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => return Err(Error::InvalidConfigFile(path, e.to_string())),
+        };
+
+        toml::from_str(&contents).map_err(|e| Error::InvalidConfigFile(path, e.message().to_owned()))
+    }
+}
+
+// interpreters used for non-executable scripts, by file extension
+const DEFAULT_INTERPRETERS: [(&str, &str); 6] = [
+    ("sh", "sh"),
+    ("bash", "bash"),
+    ("py", "python3"),
+    ("rb", "ruby"),
+    ("pl", "perl"),
+    ("js", "node"),
+];
+
 #[derive(Clone)]
 pub struct Config {
     pub name: String,
     pub color: Color,
     pub root: PathBuf,
+    pub executable: Option<PathBuf>,
     infer_long_arguments: bool,
+    pub autocorrect: bool,
+    pub infer_subcommands: bool,
+    pub default_help: DefaultHelp,
+    pub env: BTreeMap<String, String>,
+    dotenv_current_dir: bool,
+    interpreters: HashMap<String, String>,
+    libexec_paths: Vec<PathBuf>,
+    pub project_libexec_path: Option<PathBuf>,
     pub cache_directory: PathBuf,
+    pub cache: Rc<Cache>,
+    pub config_directory: PathBuf,
+    pub data_directory: PathBuf,
+    pub state_directory: PathBuf,
 }
 
 impl Config {
-    pub fn new(name: String, root: PathBuf, color: Color, infer_long_arguments: bool) -> Config {
+    pub fn new(name: String, root: PathBuf, executable: Option<PathBuf>, settings: Settings) -> Config {
+        let color = settings.color.unwrap_or(Color::Auto);
+
         let xdg_dirs = match xdg::BaseDirectories::with_prefix(&name) {
             Ok(dir) => dir,
             Err(e) => {
-                println!("Problem determining XDG base directory");
-                println!("Original error: {}", e);
-                exit(1);
+                report(&name, color, Severity::Error, &format!("problem determining XDG base directory: {}", e));
+                exit(EXIT_FAILURE);
             }
         };
         let cache_directory = match xdg_dirs.create_cache_directory("cache") {
             Ok(dir) => dir,
             Err(e) => {
-                println!("Problem determining XDG cache directory");
-                println!("Original error: {}", e);
-                exit(1);
+                report(&name, color, Severity::Error, &format!("problem determining XDG cache directory: {}", e));
+                exit(EXIT_FAILURE);
+            }
+        };
+
+        // sub's own files stay out of the cache directory handed to scripts
+        let index_path = match xdg::BaseDirectories::with_prefix("sub") {
+            Ok(dirs) => dirs.get_cache_home().join(&name).join("index.json"),
+            Err(e) => {
+                report(&name, color, Severity::Error, &format!("problem determining XDG base directory: {}", e));
+                exit(EXIT_FAILURE);
             }
         };
 
+        let mut interpreter_map: HashMap<String, String> = DEFAULT_INTERPRETERS
+            .iter()
+            .map(|(extension, interpreter)| (extension.to_string(), interpreter.to_string()))
+            .collect();
+        interpreter_map.extend(settings.interpreters);
+
+        let project_libexec_path = env::current_dir().ok().and_then(|dir| find_project_libexec(&name, &dir));
+
+        // the CLI's own commands come first so a repository can't replace them, then the
+        // project's, then shared directories in the order they were given (relative ones are
+        // inside the root), then plugins installed by the user
+        let mut libexec_paths = vec![root.join("libexec")];
+        libexec_paths.extend(project_libexec_path.clone().filter(|path| *path != root.join("libexec")));
+        libexec_paths.extend(settings.libexec_paths.iter().map(|path| root.join(path)));
+        libexec_paths.push(xdg_dirs.get_data_home().join("plugins"));
+
         Config {
             name,
             color,
-            infer_long_arguments,
+            infer_long_arguments: settings.infer_long_arguments.unwrap_or(false),
+            autocorrect: settings.autocorrect.unwrap_or(false),
+            infer_subcommands: settings.infer_subcommands.unwrap_or(false),
+            default_help: settings.default_help.unwrap_or_default(),
+            env: settings.env,
+            dotenv_current_dir: settings.dotenv_current_dir.unwrap_or(false),
+            interpreters: interpreter_map,
+            libexec_paths,
+            project_libexec_path,
             root,
+            executable,
+            cache: Rc::new(Cache::new(index_path)),
             cache_directory,
+            // only created when a script runs, see `create_directories`; collecting the components
+            // drops the trailing slash the prefix leaves behind
+            config_directory: xdg_dirs.get_config_home().components().collect(),
+            data_directory: xdg_dirs.get_data_home().components().collect(),
+            state_directory: xdg_dirs.get_state_home().components().collect(),
+        }
+    }
+
+    /// Prints a diagnostic about sub itself to stderr, keeping stdout for command output.
+    pub fn report(&self, severity: Severity, message: &str) {
+        report(&self.name, self.color, severity, message);
+    }
+
+    /// Creates the XDG config, data and state directories handed to scripts.
+    pub fn create_directories(&self) -> Result<()> {
+        for directory in [&self.config_directory, &self.data_directory, &self.state_directory] {
+            fs::create_dir_all(directory).map_err(|e| Error::SubCommandIoError(Rc::new(e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// The `.env` files loaded into scripts' environments, from lowest to highest precedence.
+    pub fn dotenv_paths(&self) -> Vec<PathBuf> {
+        let mut paths = vec![self.root.join(".env"), self.config_directory.join(".env")];
+
+        if self.dotenv_current_dir {
+            paths.extend(env::current_dir().ok().map(|dir| dir.join(".env")));
+        }
+
+        paths
+    }
+
+    /// Whether a command comes from the project found around the current directory.
+    pub fn is_project_local(&self, path: &Path) -> bool {
+        self.project_libexec_path.as_ref().is_some_and(|project| path.starts_with(project))
+    }
+
+    /// Returns the interpreter command line for a script, based on its extension.
+    pub fn interpreter_for(&self, path: &Path) -> Option<Vec<String>> {
+        let extension = path.extension()?.to_str()?;
+        let interpreter = self.interpreters.get(extension)?;
+
+        let parts: Vec<String> = interpreter.split_whitespace().map(|part| part.to_owned()).collect();
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts)
         }
     }
 
-    pub fn libexec_path(&self) -> PathBuf {
-        let mut path = self.root.clone();
-        path.push("libexec");
-        path
+    /// The directories commands are looked up in, in order of precedence. Directories that
+    /// don't exist are left out.
+    pub fn libexec_paths(&self) -> Vec<PathBuf> {
+        self.libexec_paths.iter().filter(|path| path.is_dir()).cloned().collect()
     }
 
     pub fn base_command(&self, name: &str) -> Command {
@@ -80,13 +251,51 @@ impl Config {
             .arg(Arg::new("completions").long("completions").num_args(0).help("Print completions"))
             .arg(Arg::new("validate").long("validate").num_args(0).help("Validate subcommand"))
             .arg(Arg::new("edit").short('e').long("edit").num_args(0).help("Edit command in $VISUAL or $EDITOR"))
+            .arg(Arg::new("which").long("which").num_args(0).help("Print where the command comes from"))
+            .arg(Arg::new("man").long("man").num_args(1).value_name("directory").value_parser(clap::value_parser!(PathBuf)).help("Write man pages for the command and its subcommands"))
+            .arg(Arg::new("markdown").long("markdown").num_args(0).help("Print a Markdown reference for the command and its subcommands"))
+            .arg(Arg::new("dump-spec").long("dump-spec").num_args(0).help("Print the command and its subcommands as JSON"))
+            .arg(Arg::new("tree").long("tree").num_args(0).help("Print the command and its subcommands as a tree"))
+            .arg(Arg::new("flat").long("flat").num_args(0).help("Print the full names of all commands instead of a tree"))
+            .group(ArgGroup::new("flat_group").args(["flat"]).requires("tree"))
+            .arg(Arg::new("search").long("search").num_args(1).value_name("terms").help("Search commands by keywords in their names and documentation"))
+            .arg(Arg::new("init").long("init").num_args(1).value_name("shell").value_parser(EnumValueParser::<Shell>::new()).help("Print shell code that sets up the CLI"))
+            .arg(Arg::new("rebuild-cache").long("rebuild-cache").num_args(0).help("Parse the command and its subcommands again and update the cache"))
+            .arg(Arg::new("no-cache").long("no-cache").num_args(0).conflicts_with("rebuild-cache").help("Parse commands without using the cache"))
 
             .arg(Arg::new("commands").long("commands").num_args(0).help("Print subcommands"))
             .arg(Arg::new("extension").long("extension").num_args(1).help("Filter subcommands by extension"))
-            .group(ArgGroup::new("extension_group").args(["extension"]).requires("commands"))
+            .arg(Arg::new("recursive").long("recursive").num_args(0).help("List subcommands of subcommands too"))
+            .arg(Arg::new("glob").long("glob").num_args(1).value_name("pattern").value_parser(glob::Pattern::new).help("Filter subcommands by a glob on their name"))
+            .arg(Arg::new("format").long("format").num_args(1).value_name("format").value_parser(EnumValueParser::<Format>::new()).help("Output format of subcommands [default: text]"))
+            .group(ArgGroup::new("extension_group").args(["extension", "recursive", "glob", "format"]).multiple(true).requires("commands"))
 
-            .group(ArgGroup::new("exclusion").args(["commands", "completions", "usage", "help", "validate", "edit"]).multiple(false).required(false))
+            .group(ArgGroup::new("exclusion").args(["commands", "completions", "usage", "help", "validate", "edit", "which", "init", "man", "markdown", "dump-spec", "tree", "search", "rebuild-cache"]).multiple(false).required(false))
 
             .arg(Arg::new("commands_with_args").trailing_var_arg(true).allow_hyphen_values(true).num_args(..))
     }
 }
+
+/// Walks up from `dir` looking for a project that ships its own commands in `.<name>/libexec`.
+fn find_project_libexec(name: &str, dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().map(|dir| dir.join(format!(".{}", name)).join("libexec")).find(|path| path.is_dir())
+}
+
+pub fn report(name: &str, color: Color, severity: Severity, message: &str) {
+    let (label, style) = match severity {
+        Severity::Warning => ("warning:", AnsiColor::Yellow.on_default().bold()),
+        Severity::Error => ("error:", AnsiColor::Red.on_default().bold()),
+    };
+
+    let colored = match color {
+        Color::Auto => io::stderr().is_terminal(),
+        Color::Always => true,
+        Color::Never => false,
+    };
+
+    if colored {
+        eprintln!("{}: {}{}{} {}", name, style.render(), label, style.render_reset(), message);
+    } else {
+        eprintln!("{}: {} {}", name, label, message);
+    }
+}
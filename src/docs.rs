@@ -0,0 +1,32 @@
+/// The documented arguments of a command, split into positionals and options.
+pub(crate) fn arguments(definition: &clap::Command) -> (Vec<&clap::Arg>, Vec<&clap::Arg>) {
+    definition
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set() && arg.get_id() != "commands_with_args")
+        .partition(|arg| arg.is_positional())
+}
+
+/// How a positional is shown in usage lines: `<name>`, `[name]` or `[name]...`.
+pub(crate) fn positional_label(arg: &clap::Arg) -> String {
+    let mut label = if arg.is_required_set() { format!("<{}>", value_name(arg)) } else { format!("[{}]", value_name(arg)) };
+
+    if arg.get_num_args().is_some_and(|range| range.max_values() > 1) {
+        label.push_str("...");
+    }
+
+    label
+}
+
+pub(crate) fn takes_value(arg: &clap::Arg) -> bool {
+    arg.get_num_args().is_some_and(|range| range.takes_values())
+}
+
+pub(crate) fn value_name(arg: &clap::Arg) -> String {
+    arg.get_value_names()
+        .and_then(|names| names.first().map(|name| name.to_string()))
+        .unwrap_or_else(|| arg.get_id().to_string())
+}
+
+pub(crate) fn help(arg: &clap::Arg) -> String {
+    arg.get_help().map(|help| help.to_string()).unwrap_or_default()
+}
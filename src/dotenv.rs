@@ -0,0 +1,120 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+/// Reads the variables from a `.env` file. A missing file has no variables.
+pub fn load(path: &Path) -> Result<Vec<(String, String)>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::InvalidConfigFile(path.to_owned(), e.to_string())),
+    };
+
+    parse(&contents).map_err(|message| Error::InvalidConfigFile(path.to_owned(), message))
+}
+
+/// Parses `KEY=VALUE` lines. Lines may start with `export`, blank lines and `#` comments are
+/// skipped. Single quoted values are taken literally, double quoted ones understand `\n`, `\t`,
+/// `\"` and `\\`, and unquoted ones end at a ` #` comment. Nothing is interpolated.
+fn parse(contents: &str) -> std::result::Result<Vec<(String, String)>, String> {
+    let mut variables = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").map(str::trim_start).unwrap_or(line);
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) if is_valid_key(key.trim_end()) => (key.trim_end(), value.trim_start()),
+            _ => return Err(format!("line {}: expected KEY=VALUE", index + 1)),
+        };
+
+        let value = parse_value(value).ok_or_else(|| format!("line {}: unterminated quote", index + 1))?;
+
+        variables.push((key.to_owned(), value));
+    }
+
+    Ok(variables)
+}
+
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_value(value: &str) -> Option<String> {
+    if let Some(rest) = value.strip_prefix('\'') {
+        return rest.find('\'').map(|end| rest[..end].to_owned());
+    }
+
+    if let Some(rest) = value.strip_prefix('"') {
+        let mut parsed = String::new();
+        let mut chars = rest.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Some(parsed),
+                '\\' => match chars.next()? {
+                    'n' => parsed.push('\n'),
+                    't' => parsed.push('\t'),
+                    other => parsed.push(other),
+                },
+                _ => parsed.push(c),
+            }
+        }
+
+        return None;
+    }
+
+    let value = match value.find(" #") {
+        Some(comment) => &value[..comment],
+        None => value,
+    };
+
+    Some(value.trim_end().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keys_values_quotes_and_comments() {
+        let contents = r#"
+# a comment
+PLAIN=value
+export EXPORTED=yes
+SPACED = padded # trailing comment
+SINGLE='literal $HOME \n'
+DOUBLE="line\nbreak \"quoted\""
+EMPTY=
+"#;
+
+        assert_eq!(
+            parse(contents).unwrap(),
+            vec![
+                ("PLAIN".to_owned(), "value".to_owned()),
+                ("EXPORTED".to_owned(), "yes".to_owned()),
+                ("SPACED".to_owned(), "padded".to_owned()),
+                ("SINGLE".to_owned(), "literal $HOME \\n".to_owned()),
+                ("DOUBLE".to_owned(), "line\nbreak \"quoted\"".to_owned()),
+                ("EMPTY".to_owned(), "".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        assert_eq!(parse("A=1\nnot a variable").unwrap_err(), "line 2: expected KEY=VALUE");
+        assert_eq!(parse("1A=1").unwrap_err(), "line 1: expected KEY=VALUE");
+        assert_eq!(parse("A=\"open").unwrap_err(), "line 1: unterminated quote");
+    }
+}
@@ -5,15 +5,70 @@ use chumsky::prelude::Simple;
 
 pub type Result<T> = result::Result<T, Error>;
 
+// Exit codes for failures in sub itself. They are documented in the README and must stay stable
+// across releases, since scripts may rely on them to tell sub's failures apart from a
+// subcommand's own exit code.
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_INVALID_ARGUMENTS: i32 = 64;
+pub const EXIT_INVALID_DATA: i32 = 65;
+pub const EXIT_NO_LIBEXEC: i32 = 66;
+pub const EXIT_CONFIG: i32 = 78;
+pub const EXIT_CANNOT_EXECUTE: i32 = 126;
+pub const EXIT_UNKNOWN_COMMAND: i32 = 127;
+pub const EXIT_SIGNAL_BASE: i32 = 128;
+
 #[derive(Clone)]
 pub enum Error {
     NoCompletions,
     NonExecutable(String),
     NoLibexecDir,
     SubCommandIoError(std::rc::Rc<io::Error>),
-    SubCommandInterrupted,
-    UnknownSubCommand(String),
+    SubCommandInterrupted(i32),
+    UnknownSubCommand(String, Vec<String>),
+    AmbiguousSubCommand(String, Vec<String>),
     InvalidUsageString(Vec<Simple<char>>),
     InvalidOptionString(Vec<Simple<char>>),
     InvalidUTF8,
+    InvalidConfigFile(std::path::PathBuf, String),
+    InvalidArguments(std::rc::Rc<clap::Error>),
+    DanglingSymlink,
+    SymlinkLoop,
+    MissingShebang,
+    InterpreterNotFound(String),
+    UnknownOption(String),
+    MissingSummary,
+    DuplicateName(Vec<String>),
+    Shadowed(std::path::PathBuf),
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::SubCommandInterrupted(signal) => EXIT_SIGNAL_BASE + signal,
+            // asking a command for its help or version isn't a failure
+            Error::InvalidArguments(e) if !e.use_stderr() => 0,
+            Error::InvalidArguments(_) => EXIT_INVALID_ARGUMENTS,
+            Error::InvalidUTF8 => EXIT_INVALID_DATA,
+            Error::NoLibexecDir => EXIT_NO_LIBEXEC,
+            Error::InvalidUsageString(_) | Error::InvalidOptionString(_) | Error::InvalidConfigFile(_, _) => EXIT_CONFIG,
+            Error::NonExecutable(_)
+            | Error::SubCommandIoError(_)
+            | Error::MissingShebang
+            | Error::InterpreterNotFound(_) => EXIT_CANNOT_EXECUTE,
+            Error::UnknownSubCommand(_, _) | Error::AmbiguousSubCommand(_, _) => EXIT_UNKNOWN_COMMAND,
+            Error::NoCompletions
+            | Error::DanglingSymlink
+            | Error::SymlinkLoop
+            | Error::UnknownOption(_)
+            | Error::MissingSummary
+            | Error::DuplicateName(_)
+            | Error::Shadowed(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
 }
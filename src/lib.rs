@@ -2,6 +2,18 @@
 
 mod parser;
 mod usage;
+mod lint;
+mod dotenv;
+mod docs;
 pub mod error;
 pub mod config;
+pub mod cache;
 pub mod commands;
+pub mod shell;
+pub mod man;
+pub mod markdown;
+pub mod spec;
+pub mod tree;
+pub mod listing;
+pub mod search;
+pub mod spawn;
@@ -0,0 +1,97 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+
+// same limit the Linux kernel uses before giving up with ELOOP
+const MAX_SYMLINK_HOPS: usize = 40;
+
+const BINARY_MAGICS: [&[u8]; 6] = [
+    b"\x7fELF",
+    b"\xfe\xed\xfa\xce",
+    b"\xfe\xed\xfa\xcf",
+    b"\xce\xfa\xed\xfe",
+    b"\xcf\xfa\xed\xfe",
+    b"\xca\xfe\xba\xbe",
+];
+
+pub fn is_executable(path: &Path) -> bool {
+    match fs::metadata(path) {
+        Ok(metadata) => metadata.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+pub fn check_symlink(path: &Path) -> Option<Error> {
+    if fs::metadata(path).is_ok() {
+        return None;
+    }
+
+    let mut current = path.to_path_buf();
+
+    for _ in 0..MAX_SYMLINK_HOPS {
+        match fs::read_link(&current) {
+            Ok(target) => {
+                current = current.parent().map(|parent| parent.join(&target)).unwrap_or(target);
+            }
+            Err(_) => return Some(Error::DanglingSymlink),
+        }
+    }
+
+    Some(Error::SymlinkLoop)
+}
+
+pub fn check_shebang(path: &Path) -> Option<Error> {
+    let mut header = Vec::new();
+
+    match File::open(path) {
+        Ok(file) => {
+            if file.take(1024).read_to_end(&mut header).is_err() {
+                return None;
+            }
+        }
+        Err(_) => return None,
+    }
+
+    if BINARY_MAGICS.iter().any(|magic| header.starts_with(magic)) {
+        return None;
+    }
+
+    if !header.starts_with(b"#!") {
+        return Some(Error::MissingShebang);
+    }
+
+    let line = header[2..].split(|b| *b == b'\n').next().unwrap_or_default();
+    let line = String::from_utf8_lossy(line);
+    let mut parts = line.split_whitespace();
+
+    let interpreter = match parts.next() {
+        Some(interpreter) => interpreter,
+        None => return Some(Error::MissingShebang),
+    };
+
+    if !Path::new(interpreter).is_file() {
+        return Some(Error::InterpreterNotFound(interpreter.to_owned()));
+    }
+
+    if Path::new(interpreter).file_name().is_some_and(|name| name == "env") {
+        if let Some(program) = parts.find(|part| !part.starts_with('-')) {
+            if find_in_path(program).is_none() {
+                return Some(Error::InterpreterNotFound(program.to_owned()));
+            }
+        }
+    }
+
+    None
+}
+
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+
+    env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file() && is_executable(path))
+}
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use crate::commands::{is_alias, listed_name, Command};
+use crate::config::Config;
+use crate::spec::{self, CommandNode};
+
+/// Output formats of `--commands`.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+/// Which subcommands `--commands` lists.
+#[derive(PartialEq, Eq)]
+pub struct Filter {
+    pub extension: Option<String>,
+    pub glob: Option<glob::Pattern>,
+    pub recursive: bool,
+}
+
+struct Entry {
+    // relative to the listed command, e.g. `status` or `deploy status` when recursive
+    listed_name: String,
+    // only built for JSON, it needs the full definition of the command
+    node: Option<CommandNode>,
+}
+
+/// Lists the subcommands of a command, one name per line or as a JSON array of the same nodes
+/// `--dump-spec` describes commands with.
+pub fn render(config: &Config, command: &dyn Command, filter: &Filter, format: Format) -> String {
+    let mut entries = Vec::new();
+    collect(config, command, filter, format, &[], &mut entries);
+
+    match format {
+        Format::Text => entries.iter().map(|entry| format!("{}\n", entry.listed_name)).collect(),
+        Format::Json => {
+            let nodes: Vec<CommandNode> = entries.into_iter().filter_map(|entry| entry.node).collect();
+
+            let mut output = serde_json::to_string_pretty(&nodes).expect("nodes only contain strings");
+            output.push('\n');
+            output
+        }
+    }
+}
+
+fn collect(config: &Config, command: &dyn Command, filter: &Filter, format: Format, parents: &[String], entries: &mut Vec<Entry>) {
+    for subcommand in command.subcommands() {
+        let mut names = parents.to_vec();
+        names.push(subcommand.name().to_owned());
+
+        let is_directory = subcommand.path().is_none() && !is_alias(subcommand.as_ref());
+
+        let extension_matches = match &filter.extension {
+            Some(extension) => subcommand.path().as_deref().and_then(Path::extension).is_some_and(|e| e == extension.as_str()),
+            None => true,
+        };
+
+        let glob_matches = match &filter.glob {
+            Some(glob) => glob.matches(&names.join(" ")),
+            None => true,
+        };
+
+        if extension_matches && glob_matches {
+            let mut listed = names[..names.len() - 1].to_vec();
+            listed.push(listed_name(config, subcommand.as_ref()));
+
+            entries.push(Entry {
+                listed_name: listed.join(" "),
+                node: (format == Format::Json).then(|| spec::node(config, subcommand.as_ref())),
+            });
+        }
+
+        if filter.recursive && is_directory {
+            collect(config, subcommand.as_ref(), filter, format, &names, entries);
+        }
+    }
+}
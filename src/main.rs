@@ -2,20 +2,45 @@ extern crate sub;
 
 extern crate clap;
 
-use clap::{Args, Parser};
+use clap::{Args, CommandFactory, Parser};
+use clap::error::ErrorKind;
 
 use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::os::unix::process::ExitStatusExt;
 
+use sub::cache;
 use sub::commands::subcommand;
-use sub::config::{Color, Config};
-use sub::error::Error;
+use sub::config::{report, Color, Config, Settings};
+use sub::error::{self, Error, Severity};
+use sub::shell::{self, Shell};
+use sub::man;
+use sub::markdown;
+use sub::spec;
+use sub::tree;
+use sub::search;
+use sub::spawn;
+use sub::listing::{self, Filter, Format};
 
 fn main() {
     let (config, cliargs) = parse_sub_cli_args();
 
     let user_cli_args = parse_user_cli_args(&config, cliargs);
 
+    // sets up the CLI as a whole, so it doesn't need a command
+    if let UserCliMode::Init(shell) = user_cli_args.mode {
+        print!("{}", shell::init(&config, shell));
+        exit(0);
+    }
+
+    if user_cli_args.no_cache {
+        config.cache.bypass();
+    }
+
+    if user_cli_args.mode == UserCliMode::RebuildCache {
+        config.cache.rebuild();
+    }
+
     let subcommand = match subcommand(&config, user_cli_args.commands_with_args.clone()) {
         Ok(subcommand) => subcommand,
         Err(error) => handle_error(
@@ -26,10 +51,15 @@ fn main() {
     };
 
     match user_cli_args.mode {
-        UserCliMode::Invoke => match subcommand.invoke() {
-            Ok(code) => exit(code),
-            Err(error) => handle_error(&config, error, false),
-        },
+        UserCliMode::Invoke => {
+            let result = subcommand.invoke();
+            config.cache.save();
+
+            match result {
+                Ok(code) => exit(code),
+                Err(error) => handle_error(&config, error, false),
+            }
+        }
         UserCliMode::Usage => {
             let usage = match subcommand.usage() {
                 Ok(usage) => usage,
@@ -46,47 +76,84 @@ fn main() {
 
             println!("{}", help);
         }
-        UserCliMode::Commands(extension) => {
-            for subcommand in subcommand.subcommands() {
-                if let Some(extension) = &extension {
-                    if let Some(subcommand_extension) = Path::new(subcommand.name()).extension() {
-                        if subcommand_extension == extension.as_str() {
-                            println!("{}", subcommand.name());
-                        }
-                    }
-                } else {
-                    println!("{}", subcommand.name());
-                }
+        UserCliMode::Commands(filter, format) => print!("{}", listing::render(&config, subcommand.as_ref(), &filter, format)),
+        UserCliMode::Completions => {
+            let result = subcommand.completions();
+            config.cache.save();
+
+            match result {
+                Ok(code) => exit(code),
+                Err(error) => handle_error(&config, error, true),
             }
         }
-        UserCliMode::Completions => match subcommand.completions() {
-            Ok(code) => exit(code),
-            Err(error) => handle_error(&config, error, true),
-        },
         UserCliMode::Validate => {
             let errors = subcommand.validate();
-            for error in &errors {
-                println!("{}: {}", error.0.display(), print_error(error.1.clone()));
+            config.cache.save();
+
+            for (path, severity, error) in &errors {
+                let severity = match severity {
+                    Severity::Warning => "warning",
+                    Severity::Error => "error",
+                };
+
+                println!("{}: {}: {}", path.display(), severity, print_error(error.clone()));
             }
 
-            if errors.is_empty() {
+            if errors.iter().all(|(_, severity, _)| *severity == Severity::Warning) {
                 exit(0);
             } else {
-                exit(1);
+                exit(error::EXIT_FAILURE);
+            }
+        }
+        UserCliMode::Man(directory) => match man::write_pages(subcommand.as_ref(), &directory) {
+            Ok(paths) => {
+                for path in paths {
+                    println!("{}", path.display());
+                }
+            }
+            Err(e) => {
+                config.report(Severity::Error, &format!("failed to write man pages: {}", e));
+                exit(error::EXIT_FAILURE);
+            }
+        },
+        UserCliMode::Markdown => print!("{}", markdown::reference(subcommand.as_ref())),
+        UserCliMode::DumpSpec => print!("{}", spec::dump(&config, subcommand.as_ref())),
+        UserCliMode::Tree { flat } => {
+            if flat {
+                print!("{}", tree::flat(subcommand.as_ref()));
+            } else {
+                print!("{}", tree::render(&config, subcommand.as_ref()));
+            }
+        }
+        UserCliMode::Search(terms) => {
+            let matches = search::search(subcommand.as_ref(), &terms);
+
+            if matches.is_empty() {
+                config.report(Severity::Error, &format!("no commands match '{}'", terms.join(" ")));
+                exit(error::EXIT_FAILURE);
+            }
+
+            print!("{}", matches);
+        }
+        UserCliMode::RebuildCache => cache::fill(subcommand.as_ref()),
+        UserCliMode::Init(_) => unreachable!("--init is handled before looking up the subcommand"),
+        UserCliMode::Which => {
+            for path in subcommand.origins() {
+                println!("{}", path.display());
             }
         }
         UserCliMode::Edit => {
             let editor = std::env::var("VISUAL")
                 .or_else(|_| std::env::var("EDITOR"))
                 .unwrap_or_else(|_| {
-                    println!("{}: no editor configured. Set $VISUAL or $EDITOR", config.name);
+                    config.report(Severity::Error, "no editor configured. Set $VISUAL or $EDITOR");
                     exit(1);
                 });
 
             let script_path = match subcommand.path() {
                 Some(path) => path,
                 None => {
-                    println!("{}: cannot edit a directory", config.name);
+                    config.report(Severity::Error, "cannot edit a directory");
                     exit(1);
                 }
             };
@@ -100,21 +167,29 @@ fn main() {
                 .arg(&script_path)
                 .status()
                 .unwrap_or_else(|e| {
-                    println!("{}: failed to run editor: {}", config.name, e);
+                    config.report(Severity::Error, &format!("failed to run editor: {}", e));
                     exit(1);
                 });
 
-            exit(status.code().unwrap_or(1));
+            match status.code() {
+                Some(code) => exit(code),
+                None => exit(error::EXIT_SIGNAL_BASE + status.signal().unwrap_or(0)),
+            }
         }
     }
+
+    // commands are parsed as their docs are needed, so the index is written once the output is
+    // done; modes that exit early write it themselves
+    config.cache.save();
 }
 
 fn print_error(error: Error) -> String {
     match error {
         Error::NoCompletions => "no completions".to_string(),
-        Error::SubCommandInterrupted => "sub command interrupted".to_string(),
+        Error::SubCommandInterrupted(signal) => format!("sub command interrupted by signal {}", signal),
         Error::NonExecutable(_) => "non-executable".to_string(),
-        Error::UnknownSubCommand(name) => format!("unknown sub command '{}'", name),
+        Error::UnknownSubCommand(name, _) => format!("unknown sub command '{}'", name),
+        Error::AmbiguousSubCommand(name, _) => format!("ambiguous sub command '{}'", name),
         Error::InvalidUsageString(errors) => {
             let mut message = "invalid usage string".to_string();
             for error in errors {
@@ -130,76 +205,135 @@ fn print_error(error: Error) -> String {
             message
         }
         Error::InvalidUTF8 => "invalid UTF-8".to_string(),
+        Error::InvalidConfigFile(path, message) => format!("invalid {}: {}", path.display(), message.trim_end()),
+        Error::InvalidArguments(e) => e.to_string(),
         Error::NoLibexecDir => "libexec directory not found in root".to_string(),
-        Error::SubCommandIoError(e) => format!("IO Error: {}", e),
+        Error::SubCommandIoError(e) => e.to_string(),
+        Error::DanglingSymlink => "dangling symlink".to_string(),
+        Error::SymlinkLoop => "symlink loop".to_string(),
+        Error::MissingShebang => "missing shebang".to_string(),
+        Error::InterpreterNotFound(name) => format!("interpreter '{}' not found", name),
+        Error::UnknownOption(name) => format!("option '{}' does not appear in usage", name),
+        Error::MissingSummary => "missing summary".to_string(),
+        Error::DuplicateName(names) => {
+            let names: Vec<String> = names.iter().map(|name| format!("'{}'", name)).collect();
+            format!("name conflicts with {}", names.join(", "))
+        }
+        Error::Shadowed(path) => format!("shadowed by {}", path.display()),
     }
 }
 
 fn handle_error(config: &Config, error: Error, silent: bool) -> ! {
+    let code = error.exit_code();
+
     match error {
-        Error::NoCompletions => exit(1),
-        Error::SubCommandInterrupted => exit(1),
-        Error::NonExecutable(_) => exit(1),
-        Error::UnknownSubCommand(name) => {
+        Error::NoCompletions => {}
+        Error::SubCommandInterrupted(signal) => spawn::reraise(signal),
+        Error::NonExecutable(name) => {
             if !silent {
-                println!("{}: no such sub command '{}'", config.name, name);
+                config.report(Severity::Error, &format!("'{}' is not executable; add +x or map its extension with --interpreter", name));
             }
-            exit(1);
         }
-        Error::InvalidUsageString(errors) => {
+        Error::InvalidArguments(e) => {
             if !silent {
-                println!("{}: invalid usage string", config.name);
-                for error in errors {
-                    println!("  {}", error);
-                }
+                let _ = e.print();
             }
-            exit(1);
         }
-        Error::InvalidOptionString(errors) => {
+        Error::UnknownSubCommand(name, suggestions) => {
             if !silent {
-                println!("{}: invalid option string", config.name);
-                for error in errors {
-                    println!("  {}", error);
+                let mut message = format!("no such sub command '{}'", name);
+
+                match suggestions.len() {
+                    0 => {}
+                    1 => message.push_str(&format!("\n\nDid you mean '{}'?", suggestions[0])),
+                    _ => {
+                        message.push_str("\n\nDid you mean one of these?");
+                        for suggestion in suggestions {
+                            message.push_str(&format!("\n    {}", suggestion));
+                        }
+                    }
                 }
+
+                config.report(Severity::Error, &message);
             }
-            exit(1);
-        }
-        Error::InvalidUTF8 => {
-            if !silent {
-                println!("invalid UTF-8");
-            }
-            exit(1);
         }
-        Error::NoLibexecDir => {
+        Error::AmbiguousSubCommand(name, candidates) => {
             if !silent {
-                println!("{}: libexec directory not found in root", config.name);
+                let mut message = format!("ambiguous sub command '{}'\n\nIt could be one of these:", name);
+                for candidate in candidates {
+                    message.push_str(&format!("\n    {}", candidate));
+                }
+
+                config.report(Severity::Error, &message);
             }
-            exit(1);
         }
-        Error::SubCommandIoError(e) => {
+        Error::InvalidUTF8
+        | Error::InvalidConfigFile(_, _)
+        | Error::NoLibexecDir
+        | Error::SubCommandIoError(_)
+        | Error::InvalidUsageString(_)
+        | Error::InvalidOptionString(_)
+        | Error::DanglingSymlink
+        | Error::SymlinkLoop
+        | Error::MissingShebang
+        | Error::InterpreterNotFound(_)
+        | Error::UnknownOption(_)
+        | Error::MissingSummary
+        | Error::DuplicateName(_)
+        | Error::Shadowed(_) => {
             if !silent {
-                println!("{}: {}", config.name, e);
+                config.report(Severity::Error, &print_error(error));
             }
-            exit(1);
         }
     }
+
+    exit(code);
 }
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct SubCli {
     #[arg(short, long)]
-    #[arg(default_value = "auto")]
-    #[arg(help = "Enable colored output for help messages")]
-    color: Color,
+    #[arg(help = "Enable colored output for help messages [default: auto]")]
+    color: Option<Color>,
 
-    #[arg(long)]
+    #[arg(long, overrides_with = "no_infer_long_arguments")]
     #[arg(help = "Allow partial matches of long arguments")]
     infer_long_arguments: bool,
 
+    #[arg(long, overrides_with = "infer_long_arguments")]
+    #[arg(help = "Require long arguments to be spelled out, even if sub.toml allows partial matches")]
+    no_infer_long_arguments: bool,
+
+    #[arg(long, overrides_with = "no_infer_subcommands")]
+    #[arg(help = "Allow unambiguous prefixes of subcommand names")]
+    infer_subcommands: bool,
+
+    #[arg(long, overrides_with = "infer_subcommands")]
+    #[arg(help = "Require full subcommand names, even if sub.toml allows prefixes")]
+    no_infer_subcommands: bool,
+
+    #[arg(long, overrides_with = "no_autocorrect")]
+    #[arg(help = "Run the only subcommand starting with an unknown name instead of failing")]
+    autocorrect: bool,
+
+    #[arg(long, overrides_with = "autocorrect")]
+    #[arg(help = "Fail on unknown subcommand names, even if sub.toml turns on autocorrect")]
+    no_autocorrect: bool,
+
+    #[arg(long = "interpreter", value_name = "EXTENSION=COMMAND")]
+    #[arg(value_parser = interpreter_mapping)]
+    #[arg(help = "Run non-executable scripts with this extension through COMMAND; can be repeated")]
+    interpreters: Vec<(String, String)>,
+
+    #[arg(long = "libexec-path", value_name = "PATH")]
+    #[arg(value_parser = absolute_path)]
+    #[arg(help = "Also look up commands in this directory, before those listed in sub.toml; can be repeated")]
+    libexec_paths: Vec<PathBuf>,
+
     #[arg(long)]
-    #[arg(help = "Sets the CLI name - used in help and error messages")]
-    name: String,
+    #[arg(help = "Sets the CLI name - used in help and error messages; required unless set in sub.toml")]
+    name: Option<String>,
 
     #[command(flatten)]
     path_args: PathArgs,
@@ -230,7 +364,6 @@ struct PathArgs {
 
 #[test]
 fn verify_cli() {
-    use clap::CommandFactory;
     SubCli::command().debug_assert();
 }
 
@@ -239,14 +372,23 @@ enum UserCliMode {
     Invoke,
     Usage,
     Help,
-    Commands(Option<String>),
+    Commands(Filter, Format),
     Completions,
     Validate,
     Edit,
+    Which,
+    Init(Shell),
+    Man(PathBuf),
+    Markdown,
+    DumpSpec,
+    Tree { flat: bool },
+    Search(Vec<String>),
+    RebuildCache,
 }
 
 struct UserCliArgs {
     mode: UserCliMode,
+    no_cache: bool,
     commands_with_args: Vec<String>,
 }
 
@@ -257,7 +399,7 @@ fn parse_user_cli_args(config: &Config, cliargs: Vec<String>) -> UserCliArgs {
         Ok(args) => args,
         Err(e) => {
             e.print().unwrap();
-            exit(1);
+            exit(if e.use_stderr() { error::EXIT_INVALID_ARGUMENTS } else { 0 });
         }
     };
 
@@ -267,7 +409,14 @@ fn parse_user_cli_args(config: &Config, cliargs: Vec<String>) -> UserCliArgs {
         } else if args.get_one::<bool>("help").cloned().unwrap_or(false) {
             UserCliMode::Help
         } else if args.get_one::<bool>("commands").cloned().unwrap_or(false) {
-            UserCliMode::Commands(args.get_one::<String>("extension").cloned())
+            UserCliMode::Commands(
+                Filter {
+                    extension: args.get_one::<String>("extension").cloned(),
+                    glob: args.get_one::<glob::Pattern>("glob").cloned(),
+                    recursive: args.get_one::<bool>("recursive").cloned().unwrap_or(false),
+                },
+                args.get_one::<Format>("format").cloned().unwrap_or(Format::Text),
+            )
         } else if args.get_one::<bool>("validate").cloned().unwrap_or(false) {
             UserCliMode::Validate
         } else if args
@@ -278,9 +427,26 @@ fn parse_user_cli_args(config: &Config, cliargs: Vec<String>) -> UserCliArgs {
             UserCliMode::Completions
         } else if args.get_one::<bool>("edit").cloned().unwrap_or(false) {
             UserCliMode::Edit
+        } else if let Some(directory) = args.get_one::<PathBuf>("man") {
+            UserCliMode::Man(directory.clone())
+        } else if args.get_one::<bool>("markdown").cloned().unwrap_or(false) {
+            UserCliMode::Markdown
+        } else if args.get_one::<bool>("dump-spec").cloned().unwrap_or(false) {
+            UserCliMode::DumpSpec
+        } else if args.get_one::<bool>("tree").cloned().unwrap_or(false) {
+            UserCliMode::Tree { flat: args.get_one::<bool>("flat").cloned().unwrap_or(false) }
+        } else if let Some(terms) = args.get_one::<String>("search") {
+            UserCliMode::Search(terms.split_whitespace().map(str::to_owned).collect())
+        } else if args.get_one::<bool>("rebuild-cache").cloned().unwrap_or(false) {
+            UserCliMode::RebuildCache
+        } else if let Some(shell) = args.get_one::<Shell>("init") {
+            UserCliMode::Init(*shell)
+        } else if args.get_one::<bool>("which").cloned().unwrap_or(false) {
+            UserCliMode::Which
         } else {
             UserCliMode::Invoke
         },
+        no_cache: args.get_one::<bool>("no-cache").cloned().unwrap_or(false),
         commands_with_args: args
             .get_many("commands_with_args")
             .map(|cmds| cmds.cloned().collect::<Vec<_>>())
@@ -291,15 +457,15 @@ fn parse_user_cli_args(config: &Config, cliargs: Vec<String>) -> UserCliArgs {
 fn parse_sub_cli_args() -> (Config, Vec<String>) {
     let args = SubCli::parse();
 
+    let executable = args.path_args.executable.as_ref().map(|path| path.canonicalize().expect("Invalid `executable` path"));
+
     let root = match args.path_args.absolute {
         Some(path) => path.clone(),
         None => {
-            let mut path = args.path_args.executable
+            let mut path = executable
+                .clone()
                 // this code is unreachable because clap is validating the arguments
-                .unwrap_or_else(|| unreachable!("Missing `executable` argument"))
-                .canonicalize()
-                .expect("Invalid `executable` path")
-                .clone();
+                .unwrap_or_else(|| unreachable!("Missing `executable` argument"));
 
             path.pop(); // remove executable name
 
@@ -311,11 +477,51 @@ fn parse_sub_cli_args() -> (Config, Vec<String>) {
         }
     };
 
-    let config = Config::new(args.name, root, args.color, args.infer_long_arguments);
+    let mut settings = match Settings::load(&root) {
+        Ok(settings) => settings,
+        Err(error) => {
+            let name = args.name.clone().unwrap_or_else(|| "sub".to_owned());
+            report(&name, args.color.unwrap_or(Color::Auto), Severity::Error, &print_error(error.clone()));
+            exit(error.exit_code());
+        }
+    };
+
+    // command line flags take precedence over sub.toml
+    let name = match args.name.or(settings.name.take()) {
+        Some(name) => name,
+        None => SubCli::command()
+            .error(ErrorKind::MissingRequiredArgument, "--name must be given unless it is set in sub.toml")
+            .exit(),
+    };
+
+    if args.color.is_some() {
+        settings.color = args.color;
+    }
+
+    settings.infer_long_arguments = flag(args.infer_long_arguments, args.no_infer_long_arguments).or(settings.infer_long_arguments);
+    settings.infer_subcommands = flag(args.infer_subcommands, args.no_infer_subcommands).or(settings.infer_subcommands);
+    settings.autocorrect = flag(args.autocorrect, args.no_autocorrect).or(settings.autocorrect);
+
+    settings.interpreters.extend(args.interpreters);
+
+    let mut libexec_paths = args.libexec_paths;
+    libexec_paths.append(&mut settings.libexec_paths);
+    settings.libexec_paths = libexec_paths;
+
+    let config = Config::new(name, root, executable, settings);
 
     (config, args.cliargs)
 }
 
+// a flag and its --no- counterpart, None when neither was given
+fn flag(yes: bool, no: bool) -> Option<bool> {
+    match (yes, no) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
 fn absolute_path(s: &str) -> Result<PathBuf, String> {
     let path = Path::new(s);
     if path.is_absolute() {
@@ -324,3 +530,12 @@ fn absolute_path(s: &str) -> Result<PathBuf, String> {
         Err("not an absolute path".to_string())
     }
 }
+
+fn interpreter_mapping(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((extension, command)) if !extension.is_empty() && !command.trim().is_empty() => {
+            Ok((extension.trim_start_matches('.').to_owned(), command.to_owned()))
+        }
+        _ => Err("expected EXTENSION=COMMAND".to_string()),
+    }
+}
@@ -0,0 +1,148 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::commands::{is_alias, Command};
+use crate::docs::{arguments, help, positional_label, takes_value, value_name};
+
+/// Writes a roff man page for a command and, recursively, for everything under it. Pages are
+/// named after the full command, so `hat deploy status` ends up in `hat-deploy-status.1`.
+pub fn write_pages(command: &dyn Command, directory: &Path) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(directory)?;
+
+    let mut written = Vec::new();
+    write_page(command, directory, &mut written)?;
+
+    Ok(written)
+}
+
+fn write_page(command: &dyn Command, directory: &Path, written: &mut Vec<PathBuf>) -> io::Result<()> {
+    // listing reads the whole directory, so it is done once for this page and the ones under it
+    let subcommands = command.subcommands();
+
+    let path = directory.join(format!("{}.1", page_name(command)));
+    fs::write(&path, render(command, &subcommands))?;
+    written.push(path);
+
+    // an alias to a directory would otherwise document its target twice, or forever if it
+    // points to one of its parents
+    if is_alias(command) {
+        return Ok(());
+    }
+
+    for subcommand in subcommands {
+        write_page(subcommand.as_ref(), directory, written)?;
+    }
+
+    Ok(())
+}
+
+fn page_name(command: &dyn Command) -> String {
+    command.definition().get_name().replace(' ', "-")
+}
+
+fn render(command: &dyn Command, subcommands: &[Box<dyn Command + '_>]) -> String {
+    let definition = command.definition();
+    let name = page_name(command);
+
+    let mut page = format!(".TH \"{}\" \"1\"\n", escape(&name.to_uppercase()));
+
+    page.push_str(".SH NAME\n");
+    match definition.get_about() {
+        Some(summary) => page.push_str(&format!("{} \\- {}\n", escape(&name), escape(&summary.to_string()))),
+        None => page.push_str(&format!("{}\n", escape(&name))),
+    }
+
+    let usage = definition.clone().render_usage().to_string();
+    let usage = usage.trim_start_matches("Usage:").trim();
+    page.push_str(&format!(".SH SYNOPSIS\n{}\n", escape(usage)));
+
+    if let Some(description) = definition.get_after_help() {
+        page.push_str(".SH DESCRIPTION\n");
+        page.push_str(&paragraphs(&description.to_string()));
+    }
+
+    let (positionals, options) = arguments(definition);
+
+    if !positionals.is_empty() {
+        page.push_str(".SH ARGUMENTS\n");
+
+        for arg in positionals {
+            page.push_str(&format!(".TP\n\\fI{}\\fR\n", escape(&positional_label(arg))));
+            push_line(&mut page, &help(arg));
+        }
+    }
+
+    if !options.is_empty() {
+        page.push_str(".SH OPTIONS\n");
+
+        for arg in options {
+            let mut flags = Vec::new();
+
+            if let Some(short) = arg.get_short() {
+                flags.push(format!("\\fB\\-{}\\fR", short));
+            }
+
+            if let Some(long) = arg.get_long() {
+                flags.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+            }
+
+            let mut label = flags.join(", ");
+
+            if takes_value(arg) {
+                label.push_str(&format!(" \\fI{}\\fR", escape(&value_name(arg))));
+            }
+
+            page.push_str(&format!(".TP\n{}\n", label));
+            push_line(&mut page, &help(arg));
+        }
+    }
+
+    if !subcommands.is_empty() {
+        page.push_str(".SH COMMANDS\n");
+
+        for subcommand in subcommands {
+            page.push_str(&format!(".TP\n\\fB{}\\fR(1)\n", escape(&page_name(subcommand.as_ref()))));
+            push_line(&mut page, &subcommand.summary());
+        }
+    }
+
+    page
+}
+
+// an empty line would show up as a blank line in the page
+fn push_line(page: &mut String, text: &str) {
+    if !text.is_empty() {
+        page.push_str(&escape(text));
+        page.push('\n');
+    }
+}
+
+// blank lines separate paragraphs, other lines are joined by roff
+fn paragraphs(text: &str) -> String {
+    let mut rendered = String::new();
+
+    for paragraph in text.split("\n\n").map(str::trim).filter(|paragraph| !paragraph.is_empty()) {
+        if !rendered.is_empty() {
+            rendered.push_str(".PP\n");
+        }
+
+        for line in paragraph.lines() {
+            rendered.push_str(&escape(line));
+            rendered.push('\n');
+        }
+    }
+
+    rendered
+}
+
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+
+    // lines starting with these would be read as requests
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
@@ -0,0 +1,117 @@
+use crate::commands::{is_alias, Command};
+use crate::docs::{arguments, help, positional_label, takes_value, value_name};
+
+/// Renders a Markdown reference for a command and everything under it: a table of contents that
+/// follows the directory nesting, then a section per command.
+pub fn reference(command: &dyn Command) -> String {
+    let mut document = section(command, 1);
+
+    let mut contents = String::new();
+    let mut sections = String::new();
+
+    for subcommand in command.subcommands() {
+        collect(subcommand.as_ref(), 0, &mut contents, &mut sections);
+    }
+
+    if !contents.is_empty() {
+        document.push_str("\n## Contents\n\n");
+        document.push_str(&contents);
+        document.push_str(&sections);
+    }
+
+    document
+}
+
+fn collect(command: &dyn Command, depth: usize, contents: &mut String, sections: &mut String) {
+    let name = full_name(command);
+
+    contents.push_str(&format!("{}- [{}](#{})", "  ".repeat(depth), name, anchor(&name)));
+    if !command.summary().is_empty() {
+        contents.push_str(&format!(": {}", command.summary()));
+    }
+    contents.push('\n');
+
+    sections.push('\n');
+    sections.push_str(&section(command, 2));
+
+    // an alias to a directory would otherwise document its target twice, or forever if it
+    // points to one of its parents
+    if is_alias(command) {
+        return;
+    }
+
+    for subcommand in command.subcommands() {
+        collect(subcommand.as_ref(), depth + 1, contents, sections);
+    }
+}
+
+fn section(command: &dyn Command, level: usize) -> String {
+    let definition = command.definition();
+
+    let mut section = format!("{} {}\n", "#".repeat(level), full_name(command));
+
+    if !command.summary().is_empty() {
+        section.push_str(&format!("\n{}\n", command.summary()));
+    }
+
+    let usage = definition.clone().render_usage().to_string();
+    section.push_str(&format!("\n```\n{}\n```\n", usage.trim()));
+
+    if let Some(description) = definition.get_after_help() {
+        section.push_str(&format!("\n{}\n", description.to_string().trim()));
+    }
+
+    let (positionals, options) = arguments(definition);
+
+    if !positionals.is_empty() {
+        section.push_str("\n| Argument | Description |\n| -------- | ----------- |\n");
+
+        for arg in positionals {
+            section.push_str(&format!("| `{}` | {} |\n", positional_label(arg), cell(&help(arg))));
+        }
+    }
+
+    if !options.is_empty() {
+        section.push_str("\n| Option | Description |\n| ------ | ----------- |\n");
+
+        for arg in options {
+            let mut flags = Vec::new();
+
+            if let Some(short) = arg.get_short() {
+                flags.push(format!("-{}", short));
+            }
+
+            if let Some(long) = arg.get_long() {
+                flags.push(format!("--{}", long));
+            }
+
+            let mut label = flags.join(", ");
+
+            if takes_value(arg) {
+                label.push_str(&format!(" <{}>", value_name(arg)));
+            }
+
+            section.push_str(&format!("| `{}` | {} |\n", label, cell(&help(arg))));
+        }
+    }
+
+    section
+}
+
+fn full_name(command: &dyn Command) -> String {
+    command.definition().get_name().to_owned()
+}
+
+// GitHub's heading anchors: lowercase, punctuation dropped, spaces turned into dashes
+fn anchor(heading: &str) -> String {
+    heading
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
@@ -1,27 +1,39 @@
 extern crate regex;
 
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader};
 use std::path::Path;
+use std::rc::Rc;
 
-fn extract_initial_comment_block(path: &Path) -> String {
-    let file = File::open(path).unwrap();
+use crate::error::{Error, Result};
 
-    let mut lines = Vec::new();
+fn extract_initial_comment_block(path: &Path) -> Result<String> {
+    let file = File::open(path).map_err(io_error)?;
+    let mut reader = BufReader::new(file);
 
-    for line in BufReader::new(file).lines() {
-        let line = line.unwrap();
+    let mut lines = Vec::new();
 
-        if line.starts_with("#") {
-            lines.push(line);
-        } else {
+    loop {
+        // looking ahead keeps a binary's first "line", which may be the whole file, from being read
+        if reader.fill_buf().map_err(io_error)?.first() != Some(&b'#') {
             break;
         }
+
+        let mut line = Vec::new();
+        reader.read_until(b'\n', &mut line).map_err(io_error)?;
+
+        let line = String::from_utf8(line).map_err(|_| Error::InvalidUTF8)?;
+        lines.push(line.trim_end_matches(['\n', '\r']).to_owned());
     }
 
-    lines.join("\n")
+    Ok(lines.join("\n"))
+}
+
+fn io_error(e: io::Error) -> Error {
+    Error::SubCommandIoError(Rc::new(e))
 }
 
 #[derive(PartialEq)]
@@ -31,26 +43,30 @@ enum Mode {
     Description,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Docs {
     pub summary: Option<String>,
     pub usage: Option<String>,
     pub options: Vec<String>,
     pub description: Option<String>,
+    pub eval: bool,
 }
 
-pub fn extract_docs(path: &Path) -> Docs {
+pub fn extract_docs(path: &Path) -> Result<Docs> {
     lazy_static! {
         static ref SUMMARY_RE: Regex = Regex::new(r"^# Summary: (.*)$").unwrap();
+        static ref EVAL_RE: Regex = Regex::new(r"^# Eval: (.*)$").unwrap();
         static ref INDENTED_RE: Regex = Regex::new(r"^# ( .*)$").unwrap();
         static ref EXTENDED_RE: Regex = Regex::new(r"^# (.*)$").unwrap();
     }
 
-    let comment_block = extract_initial_comment_block(path);
+    let comment_block = extract_initial_comment_block(path)?;
 
     let mut summary = None;
     let mut usage = None;
     let mut options = Vec::new();
     let mut description = Vec::new();
+    let mut eval = false;
 
     let mut mode = Mode::Out;
 
@@ -67,6 +83,13 @@ pub fn extract_docs(path: &Path) -> Docs {
                 }
             }
 
+            if let Some(caps) = EVAL_RE.captures(line) {
+                if let Some(m) = caps.get(1) {
+                    eval = m.as_str().trim() == "true";
+                    continue;
+                }
+            }
+
             if line.starts_with("# Usage:") {
                 usage = Some(line.to_owned());
                 continue;
@@ -114,23 +137,25 @@ pub fn extract_docs(path: &Path) -> Docs {
         }
     }
 
-    Docs {
+    Ok(Docs {
         summary,
         usage,
         options,
         description: if description.is_empty() { None } else { Some(description.join("\n")) },
-    }
+        eval,
+    })
 }
 
-pub fn provides_completions(path: &Path) -> bool {
-    let file = File::open(path).unwrap();
+pub fn provides_completions(path: &Path) -> Result<bool> {
+    let file = File::open(path).map_err(io_error)?;
 
-    for line in BufReader::new(file).lines() {
-        let line = line.unwrap();
-        if line == "# Provide completions" {
-            return true;
+    // compared as bytes, the rest of the file doesn't have to be text
+    for line in BufReader::new(file).split(b'\n') {
+        let line = line.map_err(io_error)?;
+        if line.strip_suffix(b"\r").unwrap_or(&line) == b"# Provide completions" {
+            return Ok(true);
         }
     }
 
-    false
+    Ok(false)
 }
@@ -0,0 +1,93 @@
+use crate::commands::{is_alias, Command};
+use crate::docs::{arguments, help};
+
+// how much a term counts depending on where it's found
+const NAME_WEIGHT: usize = 4;
+const SUMMARY_WEIGHT: usize = 3;
+const DESCRIPTION_WEIGHT: usize = 2;
+const OPTION_WEIGHT: usize = 1;
+
+/// Commands under a command whose name, summary, description or option descriptions contain
+/// every one of the terms, ignoring case. The best matches come first, each line showing the full
+/// command and its summary.
+pub fn search(command: &dyn Command, terms: &[String]) -> String {
+    let terms: Vec<String> = terms.iter().map(|term| term.to_lowercase()).collect();
+
+    let mut matches = Vec::new();
+    collect(command, &terms, &mut matches);
+
+    matches.sort_by(|(a_score, a_name, _), (b_score, b_name, _)| b_score.cmp(a_score).then_with(|| a_name.cmp(b_name)));
+
+    let width = matches.iter().map(|(_, name, _)| name.chars().count()).max().unwrap_or(0) + 4;
+
+    let mut output = String::new();
+
+    for (_, name, summary) in matches {
+        if summary.is_empty() {
+            output.push_str(&format!("{}\n", name));
+        } else {
+            let padding = width - name.chars().count();
+            output.push_str(&format!("{}{}{}\n", name, " ".repeat(padding), summary));
+        }
+    }
+
+    output
+}
+
+fn collect(command: &dyn Command, terms: &[String], matches: &mut Vec<(usize, String, String)>) {
+    for subcommand in command.subcommands() {
+        if let Some(score) = score(subcommand.as_ref(), terms) {
+            matches.push((score, subcommand.definition().get_name().to_owned(), subcommand.summary()));
+        }
+
+        if !is_alias(subcommand.as_ref()) {
+            collect(subcommand.as_ref(), terms, matches);
+        }
+    }
+}
+
+fn score(command: &dyn Command, terms: &[String]) -> Option<usize> {
+    let definition = command.definition();
+
+    let name = command.name().to_lowercase();
+    let summary = command.summary().to_lowercase();
+    let description = definition.get_after_help().map(|description| description.to_string().to_lowercase()).unwrap_or_default();
+
+    // directories carry sub's own options, which would match everywhere
+    let options: Vec<String> = if command.path().is_some() {
+        let (positionals, options) = arguments(definition);
+        positionals.into_iter().chain(options).map(|arg| help(arg).to_lowercase()).collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut total = 0;
+
+    for term in terms {
+        let mut found = 0;
+
+        if name.contains(term.as_str()) {
+            found += NAME_WEIGHT;
+        }
+
+        if summary.contains(term.as_str()) {
+            found += SUMMARY_WEIGHT;
+        }
+
+        if description.contains(term.as_str()) {
+            found += DESCRIPTION_WEIGHT;
+        }
+
+        if options.iter().any(|option| option.contains(term.as_str())) {
+            found += OPTION_WEIGHT;
+        }
+
+        if found == 0 {
+            return None;
+        }
+
+        total += found;
+    }
+
+    Some(total)
+}
@@ -0,0 +1,125 @@
+use std::env;
+
+use clap::ValueEnum;
+
+use crate::config::Config;
+
+/// Shells `--init` can set up.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    fn name(&self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+}
+
+/// Shell code that defines a function wrapping the CLI and registers completions for it.
+///
+/// The function gives every command a file to write shell code to through `_<NAME>_EVAL_FILE`.
+/// Only commands marked with `# Eval: true` use it, and whatever they print there is evaluated in
+/// the calling shell, which lets them change its directory or environment.
+pub fn init(config: &Config, shell: Shell) -> String {
+    let name = &config.name;
+    let prefix = format!("_{}", name.to_uppercase());
+    let function = format!("_{}_complete", name.replace('-', "_"));
+
+    let cli = cli_command(config, shell);
+
+    match shell {
+        Shell::Bash | Shell::Zsh => {
+            let mut code = format!(
+                r#"{name}() {{
+  local eval_file ret
+  eval_file="$(mktemp)"
+  {prefix}_SHELL={shell} {prefix}_EVAL_FILE="$eval_file" {cli} "$@"
+  ret=$?
+  if [ -s "$eval_file" ]; then
+    eval "$(cat "$eval_file")"
+  fi
+  rm -f "$eval_file"
+  return $ret
+}}
+"#,
+                shell = shell.name(),
+            );
+
+            if shell == Shell::Bash {
+                code.push_str(&format!(
+                    r#"
+{function}() {{
+  local IFS=$'\n'
+  COMPREPLY=($(compgen -W "$({cli} --completions "${{COMP_WORDS[@]:1:COMP_CWORD-1}}" 2>/dev/null)" -- "${{COMP_WORDS[COMP_CWORD]}}"))
+}}
+complete -F {function} {name}
+"#
+                ));
+            } else {
+                code.push_str(&format!(
+                    r#"
+{function}() {{
+  local -a completions
+  completions=("${{(@f)$({cli} --completions "${{(@)words[2,CURRENT-1]}}" 2>/dev/null)}}")
+  compadd -a completions
+}}
+if (( $+functions[compdef] )); then
+  compdef {function} {name}
+fi
+"#
+                ));
+            }
+
+            code
+        }
+        Shell::Fish => format!(
+            r#"function {name}
+    set -l eval_file (mktemp)
+    env {prefix}_SHELL=fish {prefix}_EVAL_FILE=$eval_file {cli} $argv
+    set -l ret $status
+    if test -s $eval_file
+        source $eval_file
+    end
+    rm -f $eval_file
+    return $ret
+end
+
+function {function}
+    {cli} --completions (commandline -opc)[2..-1] 2>/dev/null
+end
+complete -c {name} -f -a '({function})'
+"#
+        ),
+    }
+}
+
+// the entry point when it's known, otherwise sub itself with the same name and root
+fn cli_command(config: &Config, shell: Shell) -> String {
+    match &config.executable {
+        Some(executable) => quote(&executable.to_string_lossy(), shell),
+        None => {
+            let sub = env::current_exe().map(|path| path.to_string_lossy().into_owned()).unwrap_or_else(|_| "sub".to_owned());
+
+            format!(
+                "{} --name {} --absolute {} --",
+                quote(&sub, shell),
+                quote(&config.name, shell),
+                quote(&config.root.to_string_lossy(), shell),
+            )
+        }
+    }
+}
+
+fn quote(value: &str, shell: Shell) -> String {
+    match shell {
+        Shell::Bash | Shell::Zsh => format!("'{}'", value.replace('\'', r"'\''")),
+        Shell::Fish => format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'")),
+    }
+}
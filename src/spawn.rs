@@ -0,0 +1,236 @@
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use crate::error::{Error, Result};
+
+// signals sent to sub while it waits are relayed to the child's process group; terminal generated
+// ones (Ctrl-C, Ctrl-\) already reach the child directly because it owns the terminal
+const FORWARDED_SIGNALS: [libc::c_int; 6] = [
+    libc::SIGHUP,
+    libc::SIGINT,
+    libc::SIGQUIT,
+    libc::SIGTERM,
+    libc::SIGUSR1,
+    libc::SIGUSR2,
+];
+
+static CHILD_PGID: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn forward_signal(signal: libc::c_int) {
+    let pgid = CHILD_PGID.load(Ordering::SeqCst);
+
+    if pgid > 0 {
+        unsafe {
+            libc::kill(-pgid, signal);
+        }
+    }
+}
+
+/// Runs a command to completion while sub stays its parent.
+///
+/// The child gets its own process group, which is given the terminal when sub is in the
+/// foreground. Signals sent to sub are forwarded to that group and suspending the child suspends
+/// sub too. A child killed by a signal comes back as `Error::SubCommandInterrupted`, which sub
+/// passes on to whoever started it with `reraise` once it's done.
+pub fn status(command: &mut Command) -> Result<i32> {
+    let terminal = foreground_terminal();
+
+    // changing the foreground group from the background would otherwise stop sub
+    let previous_sigttou = unsafe { libc::signal(libc::SIGTTOU, libc::SIG_IGN) };
+
+    // signals arriving before the handlers are installed stay pending instead of killing sub
+    // and leaving the child behind
+    let previous_mask = block_forwarded_signals();
+
+    unsafe {
+        command.pre_exec(move || {
+            libc::setpgid(0, 0);
+
+            if let Some(fd) = terminal {
+                libc::tcsetpgrp(fd, libc::getpgrp());
+            }
+
+            libc::signal(libc::SIGTTOU, previous_sigttou);
+            libc::sigprocmask(libc::SIG_SETMASK, &previous_mask, std::ptr::null_mut());
+
+            Ok(())
+        });
+    }
+
+    let child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            unsafe {
+                libc::sigprocmask(libc::SIG_SETMASK, &previous_mask, std::ptr::null_mut());
+                libc::signal(libc::SIGTTOU, previous_sigttou);
+            }
+
+            return Err(Error::SubCommandIoError(Rc::new(e)));
+        }
+    };
+
+    let pid = child.id() as libc::pid_t;
+
+    // also done here in case the parent runs before the child gets to it
+    unsafe {
+        libc::setpgid(pid, pid);
+    }
+
+    if let Some(fd) = terminal {
+        unsafe {
+            libc::tcsetpgrp(fd, pid);
+        }
+    }
+
+    CHILD_PGID.store(pid, Ordering::SeqCst);
+    let previous_actions = install_forwarding();
+
+    // pending signals are delivered, and forwarded, from here on
+    unsafe {
+        libc::sigprocmask(libc::SIG_SETMASK, &previous_mask, std::ptr::null_mut());
+    }
+
+    let result = wait(pid, terminal);
+
+    restore_actions(previous_actions);
+    CHILD_PGID.store(0, Ordering::SeqCst);
+
+    if let Some(fd) = terminal {
+        unsafe {
+            libc::tcsetpgrp(fd, libc::getpgrp());
+        }
+    }
+
+    unsafe {
+        libc::signal(libc::SIGTTOU, previous_sigttou);
+    }
+
+    result
+}
+
+/// Kills sub with the signal that killed the child, so whoever started sub sees the same thing
+/// it would have seen running the script directly. Only returns if the signal doesn't end sub.
+pub fn reraise(signal: libc::c_int) {
+    unsafe {
+        // the child already dumped core if the signal does that
+        let no_core = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+        libc::setrlimit(libc::RLIMIT_CORE, &no_core);
+
+        libc::signal(signal, libc::SIG_DFL);
+
+        let mut mask: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut mask);
+        libc::sigaddset(&mut mask, signal);
+        libc::sigprocmask(libc::SIG_UNBLOCK, &mask, std::ptr::null_mut());
+
+        libc::kill(libc::getpid(), signal);
+    }
+}
+
+fn block_forwarded_signals() -> libc::sigset_t {
+    unsafe {
+        let mut mask: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut mask);
+
+        for signal in FORWARDED_SIGNALS {
+            libc::sigaddset(&mut mask, signal);
+        }
+
+        let mut previous: libc::sigset_t = std::mem::zeroed();
+        libc::sigprocmask(libc::SIG_BLOCK, &mask, &mut previous);
+
+        previous
+    }
+}
+
+fn foreground_terminal() -> Option<libc::c_int> {
+    let fd = libc::STDIN_FILENO;
+
+    unsafe {
+        if libc::isatty(fd) == 1 && libc::tcgetpgrp(fd) == libc::getpgrp() {
+            Some(fd)
+        } else {
+            None
+        }
+    }
+}
+
+fn wait(pid: libc::pid_t, terminal: Option<libc::c_int>) -> Result<i32> {
+    loop {
+        let mut status = 0;
+
+        if unsafe { libc::waitpid(pid, &mut status, libc::WUNTRACED) } == -1 {
+            let e = io::Error::last_os_error();
+
+            if e.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+
+            return Err(Error::SubCommandIoError(Rc::new(e)));
+        }
+
+        if libc::WIFEXITED(status) {
+            return Ok(libc::WEXITSTATUS(status));
+        }
+
+        if libc::WIFSIGNALED(status) {
+            return Err(Error::SubCommandInterrupted(libc::WTERMSIG(status)));
+        }
+
+        if libc::WIFSTOPPED(status) {
+            // the child was suspended (e.g. Ctrl-Z): take the terminal back and suspend sub as
+            // well so the shell sees the whole job as stopped, then resume the child with it
+            unsafe {
+                if let Some(fd) = terminal {
+                    libc::tcsetpgrp(fd, libc::getpgrp());
+                }
+
+                libc::raise(libc::SIGTSTP);
+
+                if let Some(fd) = terminal {
+                    libc::tcsetpgrp(fd, pid);
+                }
+
+                libc::kill(-pid, libc::SIGCONT);
+            }
+        }
+    }
+}
+
+fn install_forwarding() -> Vec<(libc::c_int, libc::sigaction)> {
+    let mut previous_actions = Vec::new();
+
+    for signal in FORWARDED_SIGNALS {
+        unsafe {
+            let mut previous: libc::sigaction = std::mem::zeroed();
+            libc::sigaction(signal, std::ptr::null(), &mut previous);
+
+            // respect signals ignored by whoever started sub (e.g. nohup)
+            if previous.sa_sigaction == libc::SIG_IGN {
+                continue;
+            }
+
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = forward_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+
+            libc::sigaction(signal, &action, std::ptr::null_mut());
+
+            previous_actions.push((signal, previous));
+        }
+    }
+
+    previous_actions
+}
+
+fn restore_actions(previous_actions: Vec<(libc::c_int, libc::sigaction)>) {
+    for (signal, previous) in previous_actions {
+        unsafe {
+            libc::sigaction(signal, &previous, std::ptr::null_mut());
+        }
+    }
+}
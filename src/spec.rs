@@ -0,0 +1,179 @@
+use serde::Serialize;
+
+use crate::commands::{alias_target, is_alias, Command, CompletionType};
+use crate::config::Config;
+use crate::docs::{arguments, help, value_name};
+
+/// Version of the `--dump-spec` schema. Adding fields keeps the version, removing or changing
+/// the meaning of one bumps it.
+pub const SPEC_VERSION: u32 = 1;
+
+/// What `--dump-spec` prints: a command and everything under it.
+#[derive(Serialize)]
+pub struct Spec {
+    pub version: u32,
+    /// The name of the CLI.
+    pub name: String,
+    pub command: CommandSpec,
+}
+
+/// What identifies a command, shared by `--dump-spec` and `--commands --format json`.
+#[derive(Serialize)]
+pub struct CommandNode {
+    /// The last word of the command, e.g. `status`.
+    pub name: String,
+    /// The full command, e.g. `hat deploy status`.
+    pub command: String,
+    #[serde(rename = "type")]
+    pub kind: CommandType,
+    pub paths: Vec<String>,
+    /// Where the alias points, as written in the symlink.
+    pub alias_of: Option<String>,
+    pub summary: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CommandSpec {
+    #[serde(flatten)]
+    pub node: CommandNode,
+    pub description: Option<String>,
+    pub usage: String,
+    pub eval: bool,
+    pub arguments: Vec<ArgumentSpec>,
+    pub subcommands: Vec<CommandSpec>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandType {
+    File,
+    Directory,
+    /// A symlink to a file or directory, see `alias_of`.
+    Alias,
+}
+
+#[derive(Serialize)]
+pub struct ArgumentSpec {
+    pub name: String,
+    pub kind: ArgumentKind,
+    pub short: Option<char>,
+    pub long: Option<String>,
+    pub required: bool,
+    pub exclusive: bool,
+    pub multiple: bool,
+    pub value_name: Option<String>,
+    pub description: Option<String>,
+    pub completion: Option<Completion>,
+}
+
+#[derive(Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ArgumentKind {
+    Positional,
+    Option,
+    Flag,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Completion {
+    Script,
+    Command { command: String },
+}
+
+/// Describes a command and everything under it.
+pub fn spec(config: &Config, command: &dyn Command) -> Spec {
+    Spec {
+        version: SPEC_VERSION,
+        name: config.name.to_owned(),
+        command: command_spec(config, command),
+    }
+}
+
+/// The spec of a command as pretty printed JSON, for tools that build on the CLI.
+pub fn dump(config: &Config, command: &dyn Command) -> String {
+    let mut output = serde_json::to_string_pretty(&spec(config, command)).expect("specs only contain strings, booleans and numbers");
+    output.push('\n');
+    output
+}
+
+fn command_spec(config: &Config, command: &dyn Command) -> CommandSpec {
+    let definition = command.definition();
+
+    let (positionals, options) = arguments(definition);
+
+    let arguments = positionals
+        .into_iter()
+        .chain(options)
+        .map(|arg| {
+            let kind = if arg.is_positional() {
+                ArgumentKind::Positional
+            } else if arg.get_num_args().is_some_and(|range| range.takes_values()) {
+                ArgumentKind::Option
+            } else {
+                ArgumentKind::Flag
+            };
+
+            let completion = match command.completion_type(arg.get_id().as_str()) {
+                Some(CompletionType::Script) => Some(Completion::Script),
+                Some(CompletionType::LiteralCommand(literal)) => Some(Completion::Command { command: literal }),
+                None => None,
+            };
+
+            ArgumentSpec {
+                name: arg.get_id().to_string(),
+                short: arg.get_short(),
+                long: arg.get_long().map(str::to_owned),
+                required: arg.is_required_set(),
+                exclusive: arg.is_exclusive_set(),
+                multiple: arg.get_num_args().is_some_and(|range| range.max_values() > 1),
+                value_name: (kind != ArgumentKind::Flag).then(|| value_name(arg)),
+                description: Some(help(arg)).filter(|help| !help.is_empty()),
+                completion,
+                kind,
+            }
+        })
+        .collect();
+
+    // aliases to directories aren't followed, their target is described where it lives
+    let subcommands = if is_alias(command) {
+        Vec::new()
+    } else {
+        command.subcommands().iter().map(|subcommand| command_spec(config, subcommand.as_ref())).collect()
+    };
+
+    CommandSpec {
+        node: node(config, command),
+        description: definition.get_after_help().map(|description| description.to_string()),
+        usage: definition.clone().render_usage().to_string().trim_start_matches("Usage:").trim().to_owned(),
+        eval: command.is_eval(),
+        arguments,
+        subcommands,
+    }
+}
+
+/// Identifies a command, without going into its arguments or subcommands. Only looks at the
+/// names and paths, so listing nodes doesn't parse every script.
+pub fn node(config: &Config, command: &dyn Command) -> CommandNode {
+    CommandNode {
+        name: command.names().last().unwrap_or(&config.name).to_owned(),
+        command: full_name(config, command),
+        kind: if is_alias(command) {
+            CommandType::Alias
+        } else if command.path().is_none() {
+            CommandType::Directory
+        } else {
+            CommandType::File
+        },
+        paths: command.origins().iter().map(|path| path.to_string_lossy().into_owned()).collect(),
+        alias_of: alias_target(command).map(|target| target.to_string_lossy().into_owned()),
+        summary: Some(command.summary()).filter(|summary| !summary.is_empty()),
+    }
+}
+
+/// The full command including the CLI name, e.g. `hat deploy status`.
+pub fn full_name(config: &Config, command: &dyn Command) -> String {
+    let mut names = vec![config.name.to_owned()];
+    names.extend(command.names().iter().cloned());
+    names.join(" ")
+}
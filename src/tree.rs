@@ -0,0 +1,63 @@
+use crate::commands::{alias_target, is_alias, Command};
+use crate::config::Config;
+use crate::spec::full_name;
+
+/// Draws a command and everything under it as a tree, with summaries lined up on the right.
+/// Aliases show their target and aren't expanded.
+pub fn render(config: &Config, command: &dyn Command) -> String {
+    let mut lines = vec![(full_name(config, command), command.summary())];
+
+    let subcommands = command.subcommands();
+    branches(&subcommands, "", &mut lines);
+
+    let width = lines.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0) + 4;
+
+    let mut output = String::new();
+
+    for (label, summary) in lines {
+        if summary.is_empty() {
+            output.push_str(&format!("{}\n", label));
+        } else {
+            let padding = width - label.chars().count();
+            output.push_str(&format!("{}{}{}\n", label, " ".repeat(padding), summary));
+        }
+    }
+
+    output
+}
+
+fn branches(subcommands: &[Box<dyn Command + '_>], prefix: &str, lines: &mut Vec<(String, String)>) {
+    for (index, subcommand) in subcommands.iter().enumerate() {
+        let last = index + 1 == subcommands.len();
+
+        let mut label = format!("{}{}{}", prefix, if last { "└── " } else { "├── " }, subcommand.name());
+
+        if let Some(target) = alias_target(subcommand.as_ref()) {
+            label.push_str(&format!(" -> {}", target.display()));
+        }
+
+        lines.push((label, subcommand.summary()));
+
+        if !is_alias(subcommand.as_ref()) {
+            let children = subcommand.subcommands();
+            branches(&children, &format!("{}{}", prefix, if last { "    " } else { "│   " }), lines);
+        }
+    }
+}
+
+/// Every runnable command under a command by its full name without the CLI name, one per line,
+/// e.g. `deploy status`.
+pub fn flat(command: &dyn Command) -> String {
+    let mut output = String::new();
+
+    for subcommand in command.subcommands() {
+        if subcommand.path().is_some() {
+            output.push_str(&subcommand.names().join(" "));
+            output.push('\n');
+        } else if !is_alias(subcommand.as_ref()) {
+            output.push_str(&flat(subcommand.as_ref()));
+        }
+    }
+
+    output
+}
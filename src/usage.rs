@@ -2,44 +2,45 @@ extern crate regex;
 extern crate clap;
 
 use chumsky::prelude::*;
+use serde::{Deserialize, Serialize};
 use clap::{Command, Arg};
 use clap::error::{ContextKind, ContextValue};
 
 use std::path::Path;
 use std::collections::HashMap;
 
-use crate::parser;
+use crate::parser::Docs;
 use crate::error::{Error, Result};
 use crate::config::Config;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum ArgBase {
     Positional(String),
     Short(char),
     Long(String, Option<String>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct ArgSpec {
     base: ArgBase,
     required: bool,
     exclusive: bool,
 }
 
-#[derive(Debug, PartialEq)]
-struct UsageLang {
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub(crate) struct UsageLang {
     arguments: Vec<ArgSpec>,
     rest: Option<String>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum CompletionType {
     Script,
     LiteralCommand(String),
 }
 
-#[derive(Debug, PartialEq)]
-struct OptionSpec {
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub(crate) struct OptionSpec {
     name: String,
     completion_type: Option<CompletionType>,
     description: Option<String>,
@@ -139,18 +140,27 @@ mod tests {
 pub struct Usage {
     command: Command,
     completions: HashMap<String, CompletionType>,
+    unknown_options: Vec<String>,
+    eval: bool,
     error: Option<Error>,
 }
 
 impl Usage {
-    pub fn new(command: Command, completions: HashMap<String, CompletionType>, error: Option<Error>) -> Self {
+    pub fn new(command: Command, completions: HashMap<String, CompletionType>, unknown_options: Vec<String>, eval: bool, error: Option<Error>) -> Self {
         Self {
             command,
             completions,
+            unknown_options,
+            eval,
             error,
         }
     }
 
+    /// The clap definition of the command, for generating documentation from it.
+    pub fn command(&self) -> &Command {
+        &self.command
+    }
+
     pub fn generate(&self) -> String {
         self.command.clone().render_usage().ansi().to_string()
     }
@@ -173,6 +183,15 @@ impl Usage {
         Ok(self.command.clone().render_help().ansi().to_string())
     }
 
+    pub fn unknown_options(&self) -> &[String] {
+        &self.unknown_options
+    }
+
+    /// Whether the command prints shell code for the `--init` wrapper function to evaluate.
+    pub fn is_eval(&self) -> bool {
+        self.eval
+    }
+
     pub fn provides_completions(&self) -> bool {
         !self.completions.is_empty()
     }
@@ -211,7 +230,10 @@ impl Usage {
     }
 
     pub fn parse_into_kv(&self, args: &Vec<String>) -> Result<String> {
-        let clap_args = self.command.clone().get_matches_from(args);
+        let clap_args = match self.command.clone().try_get_matches_from(args) {
+            Ok(clap_args) => clap_args,
+            Err(e) => return Err(Error::InvalidArguments(std::rc::Rc::new(e))),
+        };
 
         let mut args_parts = Vec::<String>::new();
 
@@ -233,8 +255,53 @@ impl Usage {
     }
 }
 
+/// The parsed usage and option lines of a script, kept in the cache next to its docs.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Spec {
+    usage: Option<UsageLang>,
+    options: Vec<OptionSpec>,
+}
+
+/// Parses the usage and option lines of a script's docs. Lines that don't parse are left out of
+/// the spec and reported through the error.
+pub(crate) fn parse_spec(docs: &Docs) -> (Spec, Option<Error>) {
+    // TODO: make this a vec of errors
+    let mut error = None;
+
+    let mut options = Vec::new();
+
+    for line in &docs.options {
+        match option_parser().parse(line.as_str()) {
+            Ok(option) => options.push(option),
+            Err(e) => error = Some(Error::InvalidOptionString(e)),
+        }
+    }
+
+    let mut usage = None;
+
+    if let Some(line) = &docs.usage {
+        match usage_parser().parse(line.as_str()) {
+            Ok(usage_lang) => usage = Some(usage_lang),
+            Err(e) => error = Some(Error::InvalidUsageString(e)),
+        }
+    }
+
+    (Spec { usage, options }, error)
+}
+
 pub fn extract_usage(config: &Config, path: &Path, cmd: &str) -> Usage {
-    let docs = parser::extract_docs(path);
+    let metadata = match config.cache.metadata(path) {
+        Ok(metadata) => metadata,
+        // the command still shows up in listings, everything else reports the error
+        Err(error) => return Usage::new(config.base_command(cmd).no_binary_name(true), HashMap::new(), Vec::new(), false, Some(error)),
+    };
+    let docs = metadata.docs;
+
+    // specs with errors aren't cached, parsing them again brings the errors back
+    let (spec, error) = match metadata.spec {
+        Some(spec) => (spec, None),
+        None => parse_spec(&docs),
+    };
 
     let mut command = config.base_command(cmd).no_binary_name(true);
 
@@ -246,28 +313,11 @@ pub fn extract_usage(config: &Config, path: &Path, cmd: &str) -> Usage {
         command = command.after_help(description);
     }
 
-    // TODO: make this a vec of errors
-    let mut error = None;
+    let options: HashMap<String, OptionSpec> = spec.options.into_iter().map(|option| (option.name.clone(), option)).collect();
 
-    let mut options = HashMap::<String, OptionSpec>::new();
-
-    for line in docs.options {
-        match option_parser().parse(line) {
-            Ok(option) => {
-                options.insert(option.name.clone(), option);
-            },
-            Err(e) => error = Some(Error::InvalidOptionString(e)),
-        }
-    }
-
-    if let Some(line) = docs.usage {
-        match usage_parser().parse(line) {
-            Ok(usage_lang) => {
-                command = apply_arguments(command, usage_lang, &options);
-            },
-            Err(e) => error = Some(Error::InvalidUsageString(e)),
-        }
-    } else {
+    if let Some(usage_lang) = spec.usage {
+        command = apply_arguments(command, usage_lang, &options);
+    } else if docs.usage.is_none() {
         command = command.arg(Arg::new("args").help("other arguments").trailing_var_arg(true).num_args(..).allow_hyphen_values(true));
     }
 
@@ -275,9 +325,14 @@ pub fn extract_usage(config: &Config, path: &Path, cmd: &str) -> Usage {
         spec.completion_type.as_ref().map(|completion_type| (name.clone(), completion_type.clone()))
     }).collect();
 
+    let mut unknown_options: Vec<String> = options.keys().filter(|name| {
+        !command.get_arguments().any(|arg| arg.get_id() == name.as_str())
+    }).cloned().collect();
+    unknown_options.sort();
+
     // both command and error are returned because an invalid usage string doesn't prevent the
     // command from being invoked, but it should be reported to the user
-    Usage::new(command, completions, error)
+    Usage::new(command, completions, unknown_options, docs.eval, error)
 }
 
 fn apply_arguments(mut command: Command, usage_lang: UsageLang, options: &HashMap<String, OptionSpec>) -> Command {